@@ -0,0 +1,94 @@
+//! Grouping GitHub Classroom repositories by assignment.
+//!
+//! Classroom names each student's repository `{assignment}-{username}`, so an
+//! organization ends up with dozens of repositories per assignment sharing a
+//! prefix. Nothing records which part is the assignment, though, and both
+//! assignments (`cs101-lab-01`) and usernames (`mary-jane`) can contain `-`,
+//! so we infer it. Splitting the names at each `-`, a prefix is an assignment
+//! if at least half the repositories under it are told apart by the very next
+//! part, the way usernames tell students apart. Otherwise (e.g., `cs101`,
+//! under which every repository is in `cs101-lab-01` or `cs101-lab-02`) we
+//! look for assignments under each of the longer prefixes instead.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Local};
+
+use crate::repository::{RepoKey, Repository};
+
+/// The fewest repositories that count as an assignment.
+pub const MIN_GROUP_SIZE: usize = 2;
+
+/// The repositories for one assignment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignmentGroup {
+    /// The part of the name the repositories share, without the trailing `-`.
+    pub prefix: String,
+    /// In the order the repositories were given to `group_by_assignment`.
+    pub keys: Vec<RepoKey>,
+    /// The most recent push to any of the repositories.
+    pub latest_push: DateTime<Local>
+}
+
+/// Assignment groups, in order of their prefixes, and the repositories that
+/// don't seem to belong to any assignment.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Grouping {
+    pub groups: Vec<AssignmentGroup>,
+    pub ungrouped: Vec<RepoKey>
+}
+
+// A repository's name split at each `-`, lowercased since GitHub ignores case.
+struct Named<'a> {
+    key: &'a RepoKey,
+    repo: &'a Repository,
+    parts: Vec<String>
+}
+
+#[must_use]
+pub fn group_by_assignment<'a>(repos: impl IntoIterator<Item = (&'a RepoKey, &'a Repository)>) -> Grouping {
+    let named: Vec<Named> = repos.into_iter()
+        .map(|(key, repo)| Named { key, repo, parts: repo.name.to_ascii_lowercase().split('-').map(str::to_owned).collect() })
+        .collect();
+    let mut grouping = Grouping::default();
+    group_under(0, &named.iter().collect::<Vec<_>>(), &mut grouping);
+    grouping.groups.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+    // Keep the ungrouped repositories in the order we were given them.
+    let ungrouped: BTreeSet<RepoKey> = grouping.ungrouped.drain(..).collect();
+    grouping.ungrouped = named.iter().filter(|named| ungrouped.contains(named.key)).map(|named| named.key.clone()).collect();
+    grouping
+}
+
+// `repos` all share their first `depth` parts.
+fn group_under(depth: usize, repos: &[&Named], grouping: &mut Grouping) {
+    // A repository named exactly the prefix (e.g., the assignment's starter
+    // code) has no next part, and goes with whatever the prefix turns out to be.
+    let (ended, continuing): (Vec<&Named>, Vec<&Named>) = repos.iter().partition(|named| named.parts.len() == depth);
+    let mut by_next: BTreeMap<&str, Vec<&Named>> = BTreeMap::new();
+    for named in continuing {
+        by_next.entry(named.parts[depth].as_str()).or_default().push(named);
+    }
+    let distinct = by_next.values().filter(|under| under.len() == 1).count();
+
+    if depth > 0 && repos.len() >= MIN_GROUP_SIZE && distinct * 2 >= repos.len() {
+        let first = repos[0];
+        // Take the prefix from a name rather than our lowercased parts, so it's
+        // capitalized the way the repositories are.
+        let prefix_len = first.parts[..depth].iter().map(String::len).sum::<usize>() + depth - 1;
+        grouping.groups.push(AssignmentGroup {
+            prefix: first.repo.name[..prefix_len].to_owned(),
+            keys: repos.iter().map(|named| named.key.clone()).collect(),
+            latest_push: repos.iter().map(|named| named.repo.pushed_at).max().unwrap_or(first.repo.pushed_at)
+        });
+        return
+    }
+
+    grouping.ungrouped.extend(ended.iter().map(|named| named.key.clone()));
+    for under in by_next.into_values() {
+        if under.len() >= MIN_GROUP_SIZE {
+            group_under(depth + 1, &under, grouping);
+        } else {
+            grouping.ungrouped.extend(under.iter().map(|named| named.key.clone()));
+        }
+    }
+}
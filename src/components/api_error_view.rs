@@ -0,0 +1,67 @@
+use yew::prelude::*;
+
+use crate::services::github_api::ApiError;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub error: ApiError,
+    // What we were trying to do, e.g., "load page 3 of the repositories for ice-repos".
+    pub action: String,
+    pub on_retry: Callback<MouseEvent>
+}
+
+fn explain(error: &ApiError) -> Html {
+    match error {
+        ApiError::Network(_) => html! {
+            <p>{ "We couldn't reach GitHub. Check your network connection and try again." }</p>
+        },
+        ApiError::NotFound(_) => html! {
+            <p>{ "GitHub couldn't find that. Check the spelling of the organization name, and that you're a member if it's private." }</p>
+        },
+        ApiError::Unauthorized(_) => html! {
+            <p>{ "GitHub didn't accept our login; it may have expired. Try logging out and back in." }</p>
+        },
+        ApiError::Forbidden { sso_url: Some(sso_url), .. } => html! {
+            <p>
+                { "This organization uses SAML single sign-on, and you'll need to " }
+                <a class="link" href={ sso_url.clone() } target="_blank">{ "authorize this app for it" }</a>
+                { " before trying again." }
+            </p>
+        },
+        ApiError::Forbidden { .. } => html! {
+            <p>{ "GitHub says you don't have permission to do that." }</p>
+        },
+        ApiError::RateLimited { retry_after, .. } => html! {
+            <p>{
+                retry_after.map_or_else(
+                    || "GitHub is limiting how fast we can make requests; wait a minute and try again.".to_owned(),
+                    |seconds| format!("GitHub is limiting how fast we can make requests; try again in {seconds} seconds."))
+            }</p>
+        },
+        ApiError::Http { status, .. } => html! {
+            <p>{ format!("GitHub had a problem handling the request (HTTP {status}); it's probably worth trying again.") }</p>
+        },
+        ApiError::MalformedBody(_) | ApiError::GraphQl(_) => html! {
+            <p>{ "GitHub sent back something we couldn't make sense of." }</p>
+        },
+    }
+}
+
+/// An inline explanation of a failed request to GitHub, with a button to try again.
+#[function_component(ApiErrorView)]
+pub fn api_error_view(props: &Props) -> Html {
+    let Props { error, action, on_retry } = props;
+
+    html! {
+        <div class="alert alert-error shadow-lg">
+            <div class="flex-col items-start">
+                <h3 class="font-bold">{ format!("We were unable to {action}.") }</h3>
+                { explain(error) }
+                <p class="text-xs">{ error.to_string() }</p>
+            </div>
+            <div class="flex-none">
+                <button class="btn btn-sm" onclick={ on_retry.clone() }>{ "Try again" }</button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::classroom::{group_by_assignment, AssignmentGroup};
+use crate::repository::{DesiredArchiveState, DesiredState, DesiredStateMap, Phase, RepoKey, SelectionMode};
+use crate::services::session::describe_age;
+use crate::components::repository_list::RepositoryList;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub repo_keys: Vec<RepoKey>,
+    pub on_checkbox_change: Callback<DesiredArchiveState>
+}
+
+fn select_group(keys: Vec<RepoKey>, selected: bool, mode: SelectionMode) -> Callback<MouseEvent> {
+    Callback::from(move |_| {
+        // The whole group changes in one step, so it can be undone in one step.
+        Dispatch::<DesiredStateMap>::new().reduce_mut(|state_map| {
+            let changes: Vec<_> = keys.iter()
+                .filter(|key| mode.can_select(state_map.get_repo(key)))
+                .map(|key| (key.clone(), DesiredState::from_paginator_state(selected, mode)))
+                .collect();
+            for err in state_map.transition_all(changes, Phase::Paginator) {
+                web_sys::console::error_1(&format!("Skipping a repository: {err}").into());
+            }
+        });
+    })
+}
+
+fn group_view(group: &AssignmentGroup, state_map: &DesiredStateMap, mode: SelectionMode, expanded: &UseStateHandle<BTreeSet<String>>, on_checkbox_change: &Callback<DesiredArchiveState>) -> Html {
+    let AssignmentGroup { prefix, keys, latest_push } = group;
+    let selected = keys.iter().filter(|key| state_map.get_desired_state(key) == Some(true)).count();
+    let is_expanded = expanded.contains(prefix);
+    let on_toggle = {
+        let expanded = expanded.clone();
+        let prefix = prefix.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_expanded = (*expanded).clone();
+            if !new_expanded.remove(&prefix) {
+                new_expanded.insert(prefix.clone());
+            }
+            expanded.set(new_expanded);
+        })
+    };
+    let (select_label, deselect_label) = match mode {
+        SelectionMode::Archive => ("Archive all", "Keep all"),
+        SelectionMode::Unarchive => ("Unarchive all", "Leave all archived"),
+    };
+
+    html! {
+        <div class="border rounded p-2">
+            <div class="flex flex-wrap gap-2 items-center">
+                <button class="btn btn-xs btn-ghost" onclick={ on_toggle }>{ if is_expanded { "▾" } else { "▸" } }</button>
+                <span class="font-semibold">{ format!("{prefix}-*") }</span>
+                <span class="badge badge-outline">{ format!("{} repositories", keys.len()) }</span>
+                <span class="badge badge-outline">{ format!("{selected} selected") }</span>
+                <span class="text-sm text-gray-500">{
+                    format!("latest push {} ({})", latest_push.format("%Y-%m-%d"), describe_age(latest_push.with_timezone(&Utc), Utc::now()))
+                }</span>
+                <div class="btn-group">
+                    <button class="btn btn-xs" onclick={ select_group(keys.clone(), true, mode) }>{ select_label }</button>
+                    <button class="btn btn-xs" onclick={ select_group(keys.clone(), false, mode) }>{ deselect_label }</button>
+                </div>
+            </div>
+            if is_expanded {
+                <RepositoryList repo_keys={ Some(keys.clone()) }
+                                empty_repo_list_message={ String::new() }
+                                on_checkbox_change={ on_checkbox_change.clone() } />
+            }
+        </div>
+    }
+}
+
+/// The repositories in `repo_keys` grouped by GitHub Classroom assignment,
+/// with a count and the latest push for each, and buttons to archive or keep
+/// a whole assignment. Expanding a group shows its repositories' cards.
+#[function_component(AssignmentGroups)]
+pub fn assignment_groups(props: &Props) -> Html {
+    let Props { repo_keys, on_checkbox_change } = props;
+    let (state_map, _) = use_store::<DesiredStateMap>();
+    let (mode, _) = use_store::<SelectionMode>();
+    let expanded = use_state(BTreeSet::<String>::new);
+
+    let grouping = group_by_assignment(repo_keys.iter().map(|key| (key, state_map.get_repo(key))));
+    if grouping.groups.is_empty() {
+        return html! { <p>{ "None of the repositories loaded so far share an assignment prefix" }</p> }
+    }
+
+    html! {
+        <div class="flex flex-col gap-2">
+            { for grouping.groups.iter().map(|group| group_view(group, &state_map, *mode, &expanded, on_checkbox_change)) }
+            if !grouping.ungrouped.is_empty() {
+                <p class="font-semibold mt-2">{ format!("Not part of an assignment ({})", grouping.ungrouped.len()) }</p>
+                <RepositoryList repo_keys={ Some(grouping.ungrouped.clone()) }
+                                empty_repo_list_message={ String::new() }
+                                on_checkbox_change={ on_checkbox_change.clone() } />
+            }
+        </div>
+    }
+}
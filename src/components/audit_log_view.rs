@@ -0,0 +1,67 @@
+use chrono::{Local, Utc};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::repository::DesiredState;
+use crate::services::audit_log::{AuditBatch, AuditLog};
+use crate::services::download::download;
+
+fn batch_summary(batch: &AuditBatch) -> String {
+    let failed = batch.entries.iter().filter(|entry| entry.final_state == DesiredState::Failed).count();
+    format!("{}: {} repositories in {} by {}; {} recorded, {failed} failed{}",
+        batch.started_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+        batch.repos.len(),
+        batch.organizations.join(", "),
+        batch.login.as_deref().unwrap_or("an unknown user"),
+        batch.entries.len(),
+        if batch.finished_at.is_none() { " (interrupted, or still running)" } else { "" })
+}
+
+/// The log of every batch submitted from this browser, which can be
+/// downloaded as JSON or Markdown.
+#[function_component(AuditLogView)]
+pub fn audit_log_view() -> Html {
+    let (log, dispatch) = use_store::<AuditLog>();
+
+    // The log lives in local storage, so it may have batches from earlier
+    // visits that the store doesn't know about yet.
+    use_effect_with_deps(
+        move |()| {
+            dispatch.set(AuditLog::load());
+            || ()
+        },
+        ()
+    );
+
+    let download_as = |extension: &'static str, mime_type: &'static str, render: fn(&AuditLog) -> String| -> Callback<MouseEvent> {
+        let log = log.clone();
+        Callback::from(move |_| {
+            let file_name = format!("ice-repos-audit-log-{}.{extension}", Utc::now().format("%Y%m%d-%H%M%S"));
+            download(&file_name, mime_type, &render(&log));
+        })
+    };
+    let empty = log.batches.is_empty();
+
+    html! {
+        <div class="mt-6">
+            <h3 class="text-lg">{ "Audit log" }</h3>
+            if empty {
+                <p>{ "No batches have been submitted from this browser." }</p>
+            } else {
+                <ul class="list-disc ml-6">
+                    { for log.batches.iter().rev().map(|batch| html! { <li>{ batch_summary(batch) }</li> }) }
+                </ul>
+            }
+            <div class="btn-group mt-2">
+                <button class="btn btn-sm" disabled={ empty }
+                        onclick={ download_as("json", "application/json", AuditLog::to_json) }>
+                    { "Download log (JSON)" }
+                </button>
+                <button class="btn btn-sm" disabled={ empty }
+                        onclick={ download_as("md", "text/markdown", AuditLog::to_markdown) }>
+                    { "Download log (Markdown)" }
+                </button>
+            </div>
+        </div>
+    }
+}
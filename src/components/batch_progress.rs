@@ -0,0 +1,75 @@
+use yew::prelude::*;
+
+use crate::services::archive_repos::ArchiveOutcome;
+use crate::services::batch::{BatchProgress, RepoProgress};
+
+#[derive(Clone, PartialEq, Eq, Properties)]
+pub struct Props {
+    pub progress: BatchProgress
+}
+
+fn describe_outcome(outcome: &ArchiveOutcome) -> Html {
+    match outcome {
+        ArchiveOutcome::Archived => html! { <span class="text-green-700">{ "Archived" }</span> },
+        ArchiveOutcome::AlreadyArchived => html! { <span class="text-gray-500">{ "Already archived" }</span> },
+        ArchiveOutcome::Unarchived => html! { <span class="text-green-700">{ "Unarchived" }</span> },
+        ArchiveOutcome::NotArchived => html! { <span class="text-gray-500">{ "Wasn't archived" }</span> },
+        ArchiveOutcome::PermissionDenied => html! { <span class="text-red-700">{ "Permission denied" }</span> },
+        ArchiveOutcome::NotFound => html! { <span class="text-red-700">{ "Not found" }</span> },
+        ArchiveOutcome::Error(message) => html! { <span class="text-red-700">{ format!("Error: {message}") }</span> },
+        ArchiveOutcome::RetriesExhausted(message) => html! { <span class="text-orange-700">{ format!("Gave up retrying: {message}") }</span> },
+    }
+}
+
+fn describe_progress(progress: &RepoProgress) -> Html {
+    match progress {
+        RepoProgress::Queued => html! { <span class="text-gray-500">{ "Queued" }</span> },
+        RepoProgress::InFlight => html! { <span class="text-blue-700">{ "In progress..." }</span> },
+        RepoProgress::Finished(result) => describe_outcome(&result.outcome),
+        RepoProgress::Cancelled => html! { <span class="text-gray-500">{ "Cancelled" }</span> },
+    }
+}
+
+/// Show how far along the current batch of (un)archive requests is, along
+/// with the status of each repository in it.
+#[function_component(BatchProgressView)]
+pub fn batch_progress_view(props: &Props) -> Html {
+    let Props { progress } = props;
+
+    html! {
+        <div>
+            <progress class="progress progress-primary w-full"
+                      value={ progress.completed().to_string() }
+                      max={ progress.total().to_string() }></progress>
+            <p>{ format!("{} of {} repositories processed", progress.completed(), progress.total()) }</p>
+            <table class="table table-compact w-full">
+                <thead>
+                    <tr>
+                        <th>{ "Repository" }</th>
+                        <th>{ "Status" }</th>
+                        <th>{ "Retries" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for progress.entries.iter().map(|entry| html! {
+                        <tr key={ entry.key.to_string() }>
+                            <td>{ format!("{}/{}", entry.key.organization, entry.name) }</td>
+                            <td>
+                                { describe_progress(&entry.progress) }
+                                if let Some(message) = &entry.not_recorded {
+                                    <p class="text-red-700">{ format!("but we couldn't record that here: {message}") }</p>
+                                }
+                            </td>
+                            <td>{
+                                match &entry.progress {
+                                    RepoProgress::Finished(result) => result.retries.to_string(),
+                                    _ => String::new()
+                                }
+                            }</td>
+                        </tr>
+                    }) }
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::Local;
+use yew::prelude::*;
+use yewdux::prelude::{use_store, Dispatch};
+
+use crate::services::etag_cache::{refresh_summary, EtagCache, EtagCacheSummary};
+
+/// Show what's in the cache of repository pages, and let the user throw it
+/// away if they suspect it's stale.
+#[function_component(EtagCacheStatus)]
+pub fn etag_cache_status() -> Html {
+    let (summary, _) = use_store::<EtagCacheSummary>();
+
+    // The cache lives in local storage, so it may already have entries from an
+    // earlier visit that the store doesn't know about yet.
+    use_effect_with_deps(
+        |()| {
+            if !Dispatch::<EtagCacheSummary>::new().get().loaded {
+                refresh_summary();
+            }
+            || ()
+        },
+        ()
+    );
+
+    let onclick: Callback<MouseEvent> = Callback::from(|_| EtagCache::clear());
+
+    html! {
+        <div class="text-sm">
+            <details>
+                <summary>{
+                    format!("Cached repository pages: {} ({} KB); {} served from the cache this session",
+                        summary.entries.len(), summary.total_bytes() / 1024, summary.hits)
+                }</summary>
+                <ul>
+                    { for summary.entries.iter().map(|entry| html! {
+                        <li key={ entry.url.clone() }>{
+                            format!("{} ({} KB, cached {})", entry.url, entry.bytes / 1024,
+                                entry.stored_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"))
+                        }</li>
+                    }) }
+                </ul>
+                <button class="btn btn-sm" disabled={ summary.entries.is_empty() } {onclick}>
+                    { "Clear cache" }
+                </button>
+            </details>
+        </div>
+    }
+}
@@ -0,0 +1,67 @@
+use yew::prelude::*;
+use yew_oauth2::prelude::OAuth2Context;
+use yewdux::prelude::use_store;
+
+use crate::services::github_api::{GitHubApiHandle, ListingBackend};
+use crate::services::graphql_client::GraphQlClient;
+use crate::services::rest_client::RestClient;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub children: Children
+}
+
+/// Make a `GitHubApi` client, authenticated with the current user's OAuth
+/// token, available to all the components below this one. This needs to be
+/// inside `<Authenticated>` so that there's a token to use.
+///
+/// Which client we build depends on the `ListingBackend` the user has chosen.
+#[function_component(GitHubApiProvider)]
+pub fn github_api_provider(props: &Props) -> Html {
+    let access_token = use_context::<OAuth2Context>()
+        .and_then(|context| context.access_token().map(ToOwned::to_owned))
+        .unwrap_or_default();
+    let (backend, _) = use_store::<ListingBackend>();
+    let backend = *backend;
+    let api = {
+        let access_token = access_token.clone();
+        use_state(|| make_client(backend, access_token))
+    };
+    // Yew 0.19 doesn't have `use_memo`, so we rebuild the client by hand whenever
+    // the token changes, e.g., after it's been refreshed, or the backend changes.
+    {
+        let api = api.clone();
+        use_effect_with_deps(
+            move |(access_token, backend)| {
+                api.set(make_client(*backend, access_token.clone()));
+                || ()
+            },
+            (access_token, backend)
+        );
+    }
+
+    html! {
+        <ContextProvider<GitHubApiHandle> context={ (*api).clone() }>
+            { for props.children.iter() }
+        </ContextProvider<GitHubApiHandle>>
+    }
+}
+
+fn make_client(backend: ListingBackend, access_token: String) -> GitHubApiHandle {
+    match backend {
+        ListingBackend::Rest => GitHubApiHandle::new(RestClient::new(access_token)),
+        ListingBackend::GraphQl => GitHubApiHandle::new(GraphQlClient::new(access_token)),
+    }
+}
+
+/// Get the `GitHubApi` client provided by the nearest `GitHubApiProvider`.
+///
+/// # Panics
+///
+/// Will panic if there's no `GitHubApiProvider` above this component, which
+/// is a mistake in how the component tree was put together.
+#[must_use]
+pub fn use_github_api() -> GitHubApiHandle {
+    #[allow(clippy::expect_used)]
+    use_context::<GitHubApiHandle>().expect("use_github_api called outside of a GitHubApiProvider")
+}
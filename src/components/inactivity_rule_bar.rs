@@ -0,0 +1,145 @@
+use chrono::{Local, NaiveDate};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::inactivity::{Activity, InactivityRule, InactivityRules, Threshold};
+use crate::repository::{DesiredStateMap, Phase, SelectionMode};
+
+#[derive(Clone, PartialEq, Eq, Properties)]
+pub struct Props {
+    pub organization: String
+}
+
+/// What's been typed into the bar, before it's been checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Draft {
+    activity: Activity,
+    by_date: bool,
+    days: String,
+    date: String
+}
+
+impl Default for Draft {
+    fn default() -> Self {
+        Self { activity: Activity::default(), by_date: false, days: "365".to_owned(), date: String::new() }
+    }
+}
+
+impl Draft {
+    fn rule(&self) -> Result<InactivityRule, String> {
+        let threshold = if self.by_date {
+            // Date inputs always give us ISO dates, whatever the browser shows.
+            NaiveDate::parse_from_str(&self.date, "%Y-%m-%d")
+                .map(Threshold::Before)
+                .map_err(|_| "Choose a date".to_owned())?
+        } else {
+            self.days.trim().parse().map(Threshold::DaysAgo)
+                .map_err(|_| format!("`{}` isn't a number of days", self.days.trim()))?
+        };
+        Ok(InactivityRule { activity: self.activity, threshold })
+    }
+}
+
+fn apply(organization: &str, rule: InactivityRule) {
+    Dispatch::<InactivityRules>::new().reduce_mut(|rules| rules.set(organization, rule));
+    // Everything the rule changes is one step, so it can be undone in one step.
+    Dispatch::<DesiredStateMap>::new().reduce_mut(|state_map| {
+        let changes = rule.changes(state_map.get_repos_in(organization), Local::now());
+        for err in state_map.transition_all(changes, Phase::Paginator) {
+            web_sys::console::error_1(&format!("Skipping a repository: {err}").into());
+        }
+    });
+}
+
+/// A rule to tick every repository nobody has pushed to (or updated) in a
+/// while for archiving, and untick the rest, on every page. Only makes sense
+/// when archiving, so there's nothing to see when unarchiving.
+#[function_component(InactivityRuleBar)]
+pub fn inactivity_rule_bar(props: &Props) -> Html {
+    let Props { organization } = props;
+    let (rules, rules_dispatch) = use_store::<InactivityRules>();
+    let (state_map, _) = use_store::<DesiredStateMap>();
+    let (mode, _) = use_store::<SelectionMode>();
+    let draft = use_state(Draft::default);
+
+    if *mode != SelectionMode::Archive {
+        return html! {}
+    }
+
+    let update = |change: fn(&mut Draft, String)| -> Callback<Event> {
+        let draft = draft.clone();
+        Callback::from(move |event: Event| {
+            let mut new_draft = (*draft).clone();
+            let value = event.target_dyn_into::<HtmlSelectElement>()
+                .map_or_else(|| event.target_unchecked_into::<HtmlInputElement>().value(), |select| select.value());
+            change(&mut new_draft, value);
+            draft.set(new_draft);
+        })
+    };
+    let on_activity_change = update(|draft, value| {
+        draft.activity = if value == "updated" { Activity::Updated } else { Activity::Pushed };
+    });
+    let on_kind_change = update(|draft, value| draft.by_date = value == "date");
+    let on_days_change = update(|draft, value| draft.days = value);
+    let on_date_change = update(|draft, value| draft.date = value);
+
+    let rule = draft.rule();
+    let by_days = !draft.by_date;
+    let inactive_count = rule.as_ref().map_or(0, |rule| {
+        let now = Local::now();
+        state_map.get_repos_in(organization)
+            .filter(|(_, repo)| SelectionMode::Archive.can_select(repo) && rule.is_inactive(repo, now))
+            .count()
+    });
+
+    let on_apply: Callback<MouseEvent> = {
+        let organization = organization.clone();
+        let rule = rule.clone();
+        Callback::from(move |_| {
+            if let Ok(rule) = rule {
+                apply(&organization, rule);
+            }
+        })
+    };
+    let on_stop: Callback<MouseEvent> = {
+        let organization = organization.clone();
+        Callback::from(move |_| rules_dispatch.reduce_mut(|rules| rules.remove(&organization)))
+    };
+
+    html! {
+        <div class="flex flex-col gap-1">
+            <div class="flex flex-wrap gap-2 items-center">
+                <span>{ "Select repositories not" }</span>
+                <select class="select select-bordered select-sm" onchange={ on_activity_change }>
+                    <option value="pushed" selected={ draft.activity == Activity::Pushed }>{ "pushed to" }</option>
+                    <option value="updated" selected={ draft.activity == Activity::Updated }>{ "updated" }</option>
+                </select>
+                <select class="select select-bordered select-sm" onchange={ on_kind_change }>
+                    <option value="days" selected={ by_days }>{ "in the last" }</option>
+                    <option value="date" selected={ draft.by_date }>{ "since" }</option>
+                </select>
+                if draft.by_date {
+                    <input type="date" class="input input-bordered input-sm"
+                           value={ draft.date.clone() } onchange={ on_date_change } />
+                } else {
+                    <input type="number" min="0" class="input input-bordered input-sm w-24"
+                           value={ draft.days.clone() } onchange={ on_days_change } />
+                    <span>{ "days" }</span>
+                }
+                <button class="btn btn-sm" disabled={ rule.is_err() } onclick={ on_apply }>
+                    { format!("Apply ({inactive_count} inactive)") }
+                </button>
+                if let Err(message) = &rule {
+                    <span class="text-sm text-red-700">{ message }</span>
+                }
+            </div>
+            if let Some(rule) = rules.get(organization) {
+                <p class="text-sm text-gray-500">
+                    { format!("Repositories {} are selected for archiving, including those on pages still to load. ", rule.describe()) }
+                    <button class="btn btn-xs btn-ghost" onclick={ on_stop }>{ "Stop applying this rule" }</button>
+                </p>
+            }
+        </div>
+    }
+}
@@ -1,7 +1,23 @@
 pub mod welcome;
 pub mod about;
+pub mod assignment_groups;
+pub mod api_error_view;
+pub mod audit_log_view;
+pub mod batch_progress;
+pub mod github_api_provider;
+pub mod inactivity_rule_bar;
+pub mod name_filter_bar;
 pub mod organization_entry;
+pub mod organization_tabs;
+pub mod plan_transfer;
+pub mod policy_loader;
+pub mod rate_limit_status;
+pub mod etag_cache_status;
 pub mod repository_card;
 pub mod repository_list;
 pub mod repository_paginator;
+pub mod repository_search;
 pub mod review_and_submit;
+pub mod selection_mode_toggle;
+pub mod session_manager;
+pub mod undo_redo_toolbar;
@@ -0,0 +1,97 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::name_filter::{NameFilter, NameMatcher, PatternKind};
+use crate::repository::{DesiredState, DesiredStateMap, Phase, RepoKey, SelectionMode};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub organization: String,
+    pub filter: NameFilter,
+    pub on_change: Callback<NameFilter>
+}
+
+/// The loaded repositories in `organization` that match and can be selected in `mode`.
+fn matching_keys(state_map: &DesiredStateMap, organization: &str, matcher: &NameMatcher, mode: SelectionMode) -> Vec<RepoKey> {
+    state_map.get_repos_in(organization)
+        .filter(|(_, repo)| mode.can_select(repo) && matcher.matches(&repo.name))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+/// A glob or regular expression to narrow down the repositories shown, and
+/// buttons to select or deselect every loaded repository that matches it, on
+/// every page.
+#[function_component(NameFilterBar)]
+pub fn name_filter_bar(props: &Props) -> Html {
+    let Props { organization, filter, on_change } = props;
+    let (state_map, dispatch) = use_store::<DesiredStateMap>();
+    let (mode, _) = use_store::<SelectionMode>();
+    let mode = *mode;
+    let matcher = filter.matcher();
+
+    let oninput = {
+        let filter = filter.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            on_change.emit(NameFilter { pattern: input.value(), ..filter.clone() });
+        })
+    };
+
+    let on_kind_change = {
+        let filter = filter.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            let kind = if select.value() == "regex" { PatternKind::Regex } else { PatternKind::Glob };
+            on_change.emit(NameFilter { kind, ..filter.clone() });
+        })
+    };
+
+    let matching = match &matcher {
+        Ok(matcher) if filter.is_active() => matching_keys(&state_map, organization, matcher, mode),
+        _ => Vec::new()
+    };
+
+    // Everything that matches changes in one step, so it can be undone in one step.
+    let select_matching = |selected: bool| -> Callback<MouseEvent> {
+        let matching = matching.clone();
+        let dispatch = dispatch.clone();
+        Callback::from(move |_| {
+            dispatch.reduce_mut(|state_map| {
+                let changes: Vec<_> = matching.iter()
+                    .map(|key| (key.clone(), DesiredState::from_paginator_state(selected, mode)))
+                    .collect();
+                for err in state_map.transition_all(changes, Phase::Paginator) {
+                    web_sys::console::error_1(&format!("Skipping a repository: {err}").into());
+                }
+            });
+        })
+    };
+    let nothing_matches = matching.is_empty();
+
+    html! {
+        <div class="flex flex-wrap gap-2 items-center">
+            <select class="select select-bordered select-sm" onchange={ on_kind_change }>
+                <option value="glob" selected={ filter.kind == PatternKind::Glob }>{ "Glob" }</option>
+                <option value="regex" selected={ filter.kind == PatternKind::Regex }>{ "Regex" }</option>
+            </select>
+            <input type="text" class="input input-bordered input-sm"
+                   placeholder={ match filter.kind { PatternKind::Glob => "lab-0*", PatternKind::Regex => "lab-0[1-3]-.*" } }
+                   value={ filter.pattern.clone() } {oninput} />
+            <div class="btn-group">
+                <button class="btn btn-sm" disabled={ nothing_matches } onclick={ select_matching(true) }>
+                    { format!("Select all matching ({})", matching.len()) }
+                </button>
+                <button class="btn btn-sm" disabled={ nothing_matches } onclick={ select_matching(false) }>
+                    { "Deselect all matching" }
+                </button>
+            </div>
+            if let Err(message) = &matcher {
+                <span class="text-sm text-red-700">{ message }</span>
+            }
+        </div>
+    }
+}
@@ -2,11 +2,14 @@ use std::ops::Deref;
 
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yewdux::prelude::*;
 
-use crate::repository::Organization;
+use crate::repository::Organizations;
+use crate::services::github_api::{Affiliations, ApiError, GitHubApiHandle, ListingBackend, RepoSource, Team};
+use crate::components::api_error_view::ApiErrorView;
+use crate::components::github_api_provider::use_github_api;
 
 // * Change the state when the text area loses focus instead of requiring a click on the
 //   submit button.
@@ -16,39 +19,214 @@ use crate::repository::Organization;
 //   * There's an `onsubmit` event. Would that be potentially useful?
 // * Allow the user to press "Enter" instead of having to click on "Submit"
 
+/// The kinds of `RepoSource` the user can pick from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceKind {
+    Organization,
+    AuthenticatedUser,
+    User
+}
+
+impl SourceKind {
+    fn from_value(value: &str) -> Self {
+        match value {
+            "me" => Self::AuthenticatedUser,
+            "user" => Self::User,
+            _ => Self::Organization
+        }
+    }
+
+    /// The source to add, or `None` if the form isn't filled in enough to say.
+    fn source(self, name: &str, team: Option<String>, affiliations: Affiliations) -> Option<RepoSource> {
+        match self {
+            Self::Organization if !name.is_empty() => Some(team.map_or_else(
+                || RepoSource::Organization(name.to_owned()),
+                |team| RepoSource::Team { organization: name.to_owned(), team })),
+            Self::User if !name.is_empty() => Some(RepoSource::User(name.to_owned())),
+            Self::AuthenticatedUser if !affiliations.names().is_empty() => Some(RepoSource::AuthenticatedUser(affiliations)),
+            _ => None
+        }
+    }
+}
+
+/// The teams in the organization that's been typed in, which we only look up
+/// when asked, since most people won't want them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Teams {
+    NotLoaded,
+    Loading,
+    Loaded(Vec<Team>),
+    Failed(ApiError)
+}
+
+fn load_teams(api: GitHubApiHandle, organization: String, teams: UseStateHandle<Teams>) {
+    teams.set(Teams::Loading);
+    wasm_bindgen_futures::spawn_local(async move {
+        teams.set(match api.list_teams(&organization).await {
+            Ok(loaded) => Teams::Loaded(loaded),
+            Err(err) => Teams::Failed(err)
+        });
+    });
+}
+
+fn affiliation_checkbox(label: &str, checked: bool, affiliations: &UseStateHandle<Affiliations>, set: fn(&mut Affiliations, bool)) -> Html {
+    let onchange = {
+        let affiliations = affiliations.clone();
+        Callback::from(move |event: Event| {
+            let target: HtmlInputElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let mut updated = *affiliations;
+            set(&mut updated, target.checked());
+            affiliations.set(updated);
+        })
+    };
+    html! {
+        <label class="label cursor-pointer">
+            <span class="label-text">{ label }</span>
+            <input type="checkbox" class="checkbox" {checked} {onchange} />
+        </label>
+    }
+}
+
+// Teams are optional, so until they're loaded all we show is a way to load them.
+fn team_picker(teams: &Teams, team: Option<&str>, on_load_teams: Callback<MouseEvent>, on_team_change: Callback<Event>) -> Html {
+    match teams {
+        Teams::NotLoaded => html! {
+            <button class="btn btn-sm btn-ghost" onclick={ on_load_teams }>{ "Only list one team's repositories..." }</button>
+        },
+        Teams::Loading => html! { <p>{ "Loading teams..." }</p> },
+        Teams::Failed(err) => html! {
+            <ApiErrorView error={ err.clone() } action="load the teams for this organization" on_retry={ on_load_teams } />
+        },
+        Teams::Loaded(loaded) if loaded.is_empty() => html! { <p>{ "This organization has no teams you can see." }</p> },
+        Teams::Loaded(loaded) => html! {
+            <div class="form-control">
+            <select class="select select-bordered" onchange={ on_team_change }>
+                <option value="" selected={ team.is_none() }>{ "All the organization's repositories" }</option>
+                { for loaded.iter().map(|t| html! {
+                    <option value={ t.slug.clone() } selected={ team == Some(t.slug.as_str()) }>{ &t.name }</option>
+                }) }
+            </select>
+            </div>
+        },
+    }
+}
+
 /// Controlled Text Input Component
 #[function_component(OrganizationEntry)]
 pub fn organization_entry() -> Html {
     let field_contents = use_state(|| String::from(""));
-    let (_, dispatch) = use_store::<Organization>();
+    let (_, dispatch) = use_store::<Organizations>();
+    let kind = use_state(|| SourceKind::Organization);
+    let affiliations = use_state(Affiliations::default);
+    let teams = use_state(|| Teams::NotLoaded);
+    let team = use_state(|| None::<String>);
+    let api = use_github_api();
+    let (backend, backend_dispatch) = use_store::<ListingBackend>();
 
     let oninput = {
         let field_contents = field_contents.clone();
+        let teams = teams.clone();
+        let team = team.clone();
         Callback::from(move |input_event: InputEvent| {
             field_contents.set(get_value_from_input_event(input_event));
+            // Any teams we've loaded were for the old name.
+            teams.set(Teams::NotLoaded);
+            team.set(None);
+        })
+    };
+
+    let on_load_teams: Callback<MouseEvent> = {
+        let field_contents = field_contents.clone();
+        let teams = teams.clone();
+        Callback::from(move |_| {
+            let organization = field_contents.trim().to_owned();
+            if !organization.is_empty() {
+                load_teams(api.clone(), organization, teams.clone());
+            }
+        })
+    };
+
+    let on_team_change: Callback<Event> = {
+        let team = team.clone();
+        Callback::from(move |event: Event| {
+            let target: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let slug = target.value();
+            team.set((!slug.is_empty()).then_some(slug));
         })
     };
 
     let onclick: Callback<MouseEvent> = {
         let field_contents = field_contents.clone();
+        let kind = *kind;
+        let affiliations = *affiliations;
+        let teams = teams.clone();
+        let team = team.clone();
         Callback::from(move |_| {
-            if !field_contents.is_empty() {
-                dispatch.set(Organization { name: Some(field_contents.deref().clone()) });
+            if let Some(source) = kind.source(field_contents.deref().trim(), (*team).clone(), affiliations) {
+                dispatch.reduce_mut(|organizations| { organizations.add_source(source); });
+                field_contents.set(String::new());
+                teams.set(Teams::NotLoaded);
+                team.set(None);
             }
         })
     };
 
+    let on_kind_change: Callback<Event> = {
+        let kind = kind.clone();
+        Callback::from(move |event: Event| {
+            let target: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            kind.set(SourceKind::from_value(&target.value()));
+        })
+    };
+
+    let onchange: Callback<Event> = Callback::from(move |event: Event| {
+        let event_target = event.target().unwrap_throw();
+        let target: HtmlSelectElement = event_target.dyn_into().unwrap_throw();
+        backend_dispatch.set(match target.value().as_str() {
+            "graphql" => ListingBackend::GraphQl,
+            _ => ListingBackend::Rest
+        });
+    });
+
     html! {
         <div class="card flex-shrink-0 w-full max-w-sm shadow-2xl bg-base-100">
             <div class="card-body">
                 <div class="form-control">
                 <label class="label">
-                    <span class="label-text">{ "What organization would you like to archive repositories for?" }</span>
+                    <span class="label-text">{ "Whose repositories would you like to archive? You can add more than one." }</span>
+                </label>
+                <select class="select select-bordered" onchange={ on_kind_change }>
+                    <option value="organization" selected={ *kind == SourceKind::Organization }>{ "An organization" }</option>
+                    <option value="me" selected={ *kind == SourceKind::AuthenticatedUser }>{ "Your account" }</option>
+                    <option value="user" selected={ *kind == SourceKind::User }>{ "Another user" }</option>
+                </select>
+                </div>
+                if *kind == SourceKind::AuthenticatedUser {
+                    <div class="form-control">
+                        { affiliation_checkbox("Repositories you own", affiliations.owner, &affiliations, |a, on| a.owner = on) }
+                        { affiliation_checkbox("Repositories you're a collaborator on", affiliations.collaborator, &affiliations, |a, on| a.collaborator = on) }
+                        { affiliation_checkbox("Repositories in your organizations", affiliations.organization_member, &affiliations, |a, on| a.organization_member = on) }
+                    </div>
+                } else {
+                    <div class="form-control">
+                    <input type="text" class="input input-bordered" {oninput} value={ (*field_contents).clone() }
+                           placeholder={ if *kind == SourceKind::User { "user" } else { "organization" } } />
+                    </div>
+                }
+                if *kind == SourceKind::Organization {
+                    { team_picker(&teams, team.as_deref(), on_load_teams, on_team_change) }
+                }
+                <div class="form-control">
+                <label class="label">
+                    <span class="label-text">{ "Load repositories using" }</span>
                 </label>
-                <input type="text" placeholder="organization" class="input input-bordered" {oninput} value={ (*field_contents).clone() }/>
+                <select class="select select-bordered" {onchange}>
+                    <option value="rest" selected={ *backend == ListingBackend::Rest }>{ "REST API" }</option>
+                    <option value="graphql" selected={ *backend == ListingBackend::GraphQl }>{ "GraphQL API (smaller responses)" }</option>
+                </select>
                 </div>
                 <div class="form-control mt-6">
-                <button type="submit" class="btn btn-primary" {onclick}>{ "Submit" }</button>
+                <button type="submit" class="btn btn-primary" {onclick}>{ "Add" }</button>
                 </div>
             </div>
         </div>
@@ -0,0 +1,55 @@
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::inactivity::InactivityRules;
+use crate::page_repo_map::PageRepoMaps;
+use crate::repository::{DesiredStateMap, Organizations};
+
+fn select(organization: &str) {
+    Dispatch::<Organizations>::new().reduce_mut(|organizations| { organizations.add(organization); });
+}
+
+// Removing an organization drops its selections, loaded pages, and
+// inactivity rule too, since there'd be no way to see or change them any more.
+fn remove(organization: &str) {
+    Dispatch::<DesiredStateMap>::new().reduce_mut(|state_map| { state_map.remove_organization(organization); });
+    Dispatch::<PageRepoMaps>::new().reduce_mut(|page_maps| page_maps.remove(organization));
+    Dispatch::<InactivityRules>::new().reduce_mut(|rules| rules.remove(organization));
+    Dispatch::<Organizations>::new().reduce_mut(|organizations| { organizations.remove(organization); });
+}
+
+/// A tab for each organization we're working on, to switch between them
+/// (keeping the selections in each) or stop working on one.
+#[function_component(OrganizationTabs)]
+pub fn organization_tabs() -> Html {
+    let (organizations, _) = use_store::<Organizations>();
+
+    if organizations.names.is_empty() {
+        return html! {}
+    }
+
+    html! {
+        <div class="tabs">
+            { for organizations.names.iter().map(|name| {
+                let class = if organizations.current.as_ref() == Some(name) { "tab tab-lifted tab-active" } else { "tab tab-lifted" };
+                let on_select = {
+                    let name = name.clone();
+                    Callback::from(move |_: MouseEvent| select(&name))
+                };
+                let on_remove = {
+                    let name = name.clone();
+                    Callback::from(move |event: MouseEvent| {
+                        event.stop_propagation();
+                        remove(&name);
+                    })
+                };
+                html! {
+                    <a class={ class } onclick={ on_select }>
+                        { name }
+                        <button class="btn btn-xs btn-ghost ml-2" title={ format!("Stop working on {name}") } onclick={ on_remove }>{ "✕" }</button>
+                    </a>
+                }
+            }) }
+        </div>
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::Local;
+use gloo::file::File;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::plan::{ImportReport, Plan, PlanEntry, PlanFormat};
+use crate::repository::{DesiredStateMap, Organizations};
+use crate::services::download::download;
+use crate::services::github_api::{ApiError, GitHubApiHandle, RepoSource};
+use crate::services::session::CurrentUser;
+use crate::components::github_api_provider::use_github_api;
+
+/// How an import went, including any entries we couldn't check because
+/// looking them up on GitHub failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ImportStatus {
+    InProgress(String),
+    Failed(String),
+    Done { file_name: String, report: ImportReport, lookup_failures: Vec<(PlanEntry, ApiError)> },
+}
+
+fn export(format: PlanFormat) {
+    let plan = Plan::from_state_map(&Dispatch::<DesiredStateMap>::new().get());
+    let organizations = Dispatch::<Organizations>::new().get().names.join("+");
+    let file_name = format!("ice-repos-plan-{organizations}-{}.{}", Local::now().format("%Y%m%d-%H%M"), format.extension());
+    download(&file_name, format.mime_type(), &plan.render(format));
+}
+
+#[allow(clippy::future_not_send)]
+async fn import(api: GitHubApiHandle, file: File, status: UseStateHandle<Option<ImportStatus>>) {
+    let file_name = file.name();
+    let text = match gloo::file::futures::read_as_text(&file).await {
+        Ok(text) => text,
+        Err(err) => { status.set(Some(ImportStatus::Failed(format!("Unable to read {file_name}: {err}")))); return }
+    };
+    // Without a recognizable extension, guess from the contents.
+    let format = PlanFormat::from_file_name(&file_name).unwrap_or_else(||
+        if text.trim_start().starts_with('{') { PlanFormat::Json } else { PlanFormat::Csv });
+    let mut plan = match Plan::parse(format, &text) {
+        Ok(plan) => plan,
+        Err(err) => { status.set(Some(ImportStatus::Failed(format!("Unable to import {file_name}: {err}")))); return }
+    };
+
+    // Entries on pages we haven't loaded yet need looking up one at a time, to
+    // see whether they still exist. A repository that's been renamed keeps its id,
+    // but one with the same name that's been deleted and recreated doesn't.
+    let organizations = Dispatch::<Organizations>::new().get().names.clone();
+    let not_loaded: Vec<PlanEntry> = plan.entries_not_in(&organizations, &Dispatch::<DesiredStateMap>::new().get())
+        .into_iter().cloned().collect();
+    let mut found = Vec::new();
+    let mut lookup_failures = Vec::new();
+    let login = Dispatch::<CurrentUser>::new().get().login.clone();
+    for entry in not_loaded {
+        // Plans don't record owners, so for the logged in user's repositories
+        // the best we can do is assume they own them.
+        let Some(key) = entry.loaded_key(&organizations) else {
+            continue
+        };
+        let owner = match &login {
+            Some(login) if entry.organization == RepoSource::AUTHENTICATED_USER_NAME => login,
+            _ => key.likely_owner()
+        };
+        match api.get_repo(owner, &entry.name).await {
+            Ok(repo) if repo.id == entry.id => found.push((key.organization, repo)),
+            Ok(_) | Err(ApiError::NotFound(_)) => (),
+            Err(err) => lookup_failures.push((entry, err)),
+        }
+    }
+    plan.entries.retain(|entry| !lookup_failures.iter().any(|(failed, _)| failed.key() == entry.key()));
+
+    let mut report = ImportReport::default();
+    Dispatch::<DesiredStateMap>::new().reduce_mut(|state_map| {
+        for (organization, repo) in found {
+            state_map.with_repos(&organization, &[repo]);
+        }
+        report = plan.apply(&organizations, state_map);
+    });
+    status.set(Some(ImportStatus::Done { file_name, report, lookup_failures }));
+}
+
+fn entry_list(title: &str, entries: &[PlanEntry]) -> Html {
+    if entries.is_empty() {
+        return html! {}
+    }
+    html! {
+        <div>
+            <p class="font-semibold">{ format!("{title} ({})", entries.len()) }</p>
+            <ul class="list-disc ml-6">
+                { for entries.iter().map(|entry| html! {
+                    <li>{ format!("{} ({}, id {})", entry.name, entry.organization, entry.id) }</li>
+                }) }
+            </ul>
+        </div>
+    }
+}
+
+fn report_view(file_name: &str, report: &ImportReport, lookup_failures: &[(PlanEntry, ApiError)]) -> Html {
+    let rejected: Vec<Html> = report.rejected.iter()
+        .map(|(entry, err)| html! { <li>{ format!("{}: {err}", entry.name) }</li> })
+        .collect();
+    let failures: Vec<Html> = lookup_failures.iter()
+        .map(|(entry, err)| html! { <li>{ format!("{}: {err}", entry.name) }</li> })
+        .collect();
+    let class = if report.has_problems() || !lookup_failures.is_empty() { "alert alert-warning" } else { "alert alert-success" };
+
+    html! {
+        <div class={ class }>
+            <div class="flex-col items-start">
+                <p>{ format!("Imported {file_name}: {} repositories changed to match the plan, {} already matched.",
+                    report.applied.len(), report.unchanged.len()) }</p>
+                { entry_list("No longer exist, or you can't see them", &report.missing) }
+                { entry_list("Archived or unarchived since the plan was made, so left alone", &report.archive_status_changed) }
+                { entry_list("For an organization you haven't added, so left alone", &report.other_organization) }
+                if !rejected.is_empty() {
+                    <p class="font-semibold">{ format!("Couldn't be changed ({})", rejected.len()) }</p>
+                    <ul class="list-disc ml-6">{ for rejected }</ul>
+                }
+                if !failures.is_empty() {
+                    <p class="font-semibold">{ format!("Couldn't be checked on GitHub ({})", failures.len()) }</p>
+                    <ul class="list-disc ml-6">{ for failures }</ul>
+                }
+            </div>
+        </div>
+    }
+}
+
+/// Export the current selection as a plan (JSON or CSV), or import one and
+/// report on any entries that no longer fit.
+#[function_component(PlanTransfer)]
+pub fn plan_transfer() -> Html {
+    let api = use_github_api();
+    let status = use_state(|| None::<ImportStatus>);
+
+    let export_as = |format: PlanFormat| -> Callback<MouseEvent> {
+        Callback::from(move |_| export(format))
+    };
+
+    let on_import: Callback<Event> = {
+        let status = status.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return
+            };
+            // Clear the input so that choosing the same file again still fires `change`.
+            input.set_value("");
+            let file = File::from(file);
+            status.set(Some(ImportStatus::InProgress(file.name())));
+            wasm_bindgen_futures::spawn_local(import(api.clone(), file, status.clone()));
+        })
+    };
+
+    let on_dismiss: Callback<MouseEvent> = {
+        let status = status.clone();
+        Callback::from(move |_| status.set(None))
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <div class="flex gap-2 items-center">
+                <div class="btn-group">
+                    <button class="btn btn-sm" onclick={ export_as(PlanFormat::Json) }>{ "Export plan (JSON)" }</button>
+                    <button class="btn btn-sm" onclick={ export_as(PlanFormat::Csv) }>{ "Export plan (CSV)" }</button>
+                </div>
+                <label class="btn btn-sm">
+                    { "Import plan" }
+                    <input type="file" class="hidden" accept=".json,.csv,application/json,text/csv" onchange={ on_import } />
+                </label>
+            </div>
+            {
+                match &*status {
+                    None => html! {},
+                    Some(ImportStatus::InProgress(file_name)) => html! { <p>{ format!("Importing {file_name}...") }</p> },
+                    Some(ImportStatus::Failed(message)) => html! {
+                        <div class="alert alert-error">
+                            <span>{ message }</span>
+                            <button class="btn btn-sm btn-ghost" onclick={ on_dismiss }>{ "Dismiss" }</button>
+                        </div>
+                    },
+                    Some(ImportStatus::Done { file_name, report, lookup_failures }) => html! {
+                        <>
+                            { report_view(file_name, report, lookup_failures) }
+                            <button class="btn btn-sm btn-ghost self-start" onclick={ on_dismiss }>{ "Dismiss" }</button>
+                        </>
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::Local;
+use gloo::file::File;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::policy::{ArchivePolicy, Policy, PolicyFormat, PolicyReport};
+use crate::repository::DesiredStateMap;
+
+/// Apply the loaded policy to everything we've loaded so far.
+fn apply() -> PolicyReport {
+    let mut archive_policy = (*Dispatch::<ArchivePolicy>::new().get()).clone();
+    let mut report = PolicyReport::default();
+    Dispatch::<DesiredStateMap>::new().reduce_mut(|state_map| report = archive_policy.apply(state_map, Local::now()));
+    Dispatch::<ArchivePolicy>::new().set(archive_policy);
+    for err in &report.rejected {
+        web_sys::console::error_1(&format!("Skipping a repository: {err}").into());
+    }
+    report
+}
+
+#[allow(clippy::future_not_send)]
+async fn load(file: File, status: UseStateHandle<Option<Result<PolicyReport, String>>>) {
+    let file_name = file.name();
+    let text = match gloo::file::futures::read_as_text(&file).await {
+        Ok(text) => text,
+        Err(err) => { status.set(Some(Err(format!("Unable to read {file_name}: {err}")))); return }
+    };
+    // Without a recognizable extension, guess from the contents.
+    let format = PolicyFormat::from_file_name(&file_name).unwrap_or_else(||
+        if text.trim_start().starts_with('{') { PolicyFormat::Json } else { PolicyFormat::Toml });
+    match Policy::parse(format, &text) {
+        Ok(policy) => {
+            Dispatch::<ArchivePolicy>::new().reduce_mut(|archive_policy| archive_policy.load(&file_name, policy));
+            status.set(Some(Ok(apply())));
+        }
+        Err(err) => status.set(Some(Err(format!("Unable to load {file_name}: {err}")))),
+    }
+}
+
+/// Load an archive policy (JSON or TOML) that decides which repositories to
+/// archive and keep, on every page, and say how it went.
+#[function_component(PolicyLoader)]
+pub fn policy_loader() -> Html {
+    let (archive_policy, dispatch) = use_store::<ArchivePolicy>();
+    let status = use_state(|| None::<Result<PolicyReport, String>>);
+
+    let on_load: Callback<Event> = {
+        let status = status.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return
+            };
+            // Clear the input so that choosing the same file again still fires `change`.
+            input.set_value("");
+            wasm_bindgen_futures::spawn_local(load(File::from(file), status.clone()));
+        })
+    };
+
+    let on_reapply: Callback<MouseEvent> = {
+        let status = status.clone();
+        Callback::from(move |_| status.set(Some(Ok(apply()))))
+    };
+
+    // Selections the policy made stay as they are, so this can be undone separately.
+    let on_remove: Callback<MouseEvent> = {
+        let status = status.clone();
+        Callback::from(move |_| {
+            dispatch.reduce_mut(ArchivePolicy::clear);
+            status.set(None);
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <div class="flex flex-wrap gap-2 items-center">
+                <label class="btn btn-sm">
+                    { if archive_policy.is_loaded() { "Load another policy" } else { "Load archive policy" } }
+                    <input type="file" class="hidden" accept=".json,.toml,application/json,application/toml" onchange={ on_load } />
+                </label>
+                if let Some(file_name) = &archive_policy.file_name {
+                    <span class="text-sm">{ format!("Using {file_name} ({} rules)", archive_policy.policy.rules.len()) }</span>
+                    <button class="btn btn-sm btn-ghost" onclick={ on_reapply }>{ "Apply again" }</button>
+                    <button class="btn btn-sm btn-ghost" onclick={ on_remove }>{ "Stop using it" }</button>
+                }
+            </div>
+            {
+                match &*status {
+                    None => html! {},
+                    Some(Err(message)) => html! { <div class="alert alert-error"><span>{ message }</span></div> },
+                    Some(Ok(report)) => html! {
+                        <p class="text-sm text-gray-500">{
+                            format!("The policy selected {} repositories to archive and kept {}; {} matched no rule and were left alone.",
+                                report.archive, report.keep, report.unmatched)
+                        }</p>
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,31 @@
+use chrono::Local;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+use crate::services::rate_limit::RateLimit;
+
+/// Show how much of our GitHub API budget is left, and whether we're
+/// currently holding requests back until it resets.
+#[function_component(RateLimitStatus)]
+pub fn rate_limit_status() -> Html {
+    let (rate_limit, _) = use_store::<RateLimit>();
+
+    let (Some(limit), Some(remaining)) = (rate_limit.limit, rate_limit.remaining) else {
+        return html! {}
+    };
+    let resets_at = rate_limit.reset
+        .map(|reset| reset.with_timezone(&Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default();
+
+    html! {
+        <div class="text-sm">
+            <p>{ format!("GitHub API requests remaining: {remaining}/{limit} (resets at {resets_at})") }</p>
+            if let Some(paused_until) = rate_limit.paused_until {
+                <p class="text-orange-700">{
+                    format!("We're almost out of GitHub API budget, so requests are paused until {}.",
+                        paused_until.with_timezone(&Local).format("%H:%M:%S"))
+                }</p>
+            }
+        </div>
+    }
+}
@@ -1,35 +1,59 @@
+use chrono::Utc;
 use wasm_bindgen::{UnwrapThrowExt, JsCast};
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
-use crate::repository::{Repository, DesiredArchiveState};
+use crate::repository::{Repository, DesiredArchiveState, RepoKey, SelectionMode};
+use crate::services::session::describe_age;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
+    pub repo_key: RepoKey,
     // TODO: Having to clone the repository in `RepositoryList` is annoying and it
     // would be cool to turn this into a reference without making a mess of the
     // memory management.
     pub repository: Repository,
-    // If this is the None variant, then this repository should already be archived.
-    // If it's a Some variant, then the enclosed boolean should indicate the desired
-    // state for this repository.
+    // If it's a Some variant, then the enclosed boolean indicates whether this
+    // repository is selected to be changed (archived or unarchived, depending
+    // on the mode).
     pub desired_archive_state: Option<bool>,
+    // The archive policy rule that decided the repository's state, if any.
+    pub decided_by: Option<String>,
+    // Whether the checkbox (if any) is for archiving or unarchiving.
+    pub mode: SelectionMode,
+    // Whether the checkbox (if any) can't be changed right now.
+    #[prop_or_default]
+    pub disabled: bool,
     pub on_checkbox_change: Callback<DesiredArchiveState>
 }
 
+fn badges(repository: &Repository) -> Html {
+    html! {
+        <div class="flex flex-wrap gap-1">
+            <span class="badge badge-outline">{ repository.visibility().label() }</span>
+            if repository.fork {
+                <span class="badge badge-outline">{ "fork" }</span>
+            }
+            if repository.is_template {
+                <span class="badge badge-outline">{ "template" }</span>
+            }
+            if let Some(language) = &repository.language {
+                <span class="badge badge-primary">{ language }</span>
+            }
+            { for repository.topics.iter().map(|topic| html! {
+                <span class="badge badge-ghost">{ topic }</span>
+            }) }
+        </div>
+    }
+}
+
 #[function_component(RepositoryCard)]
 pub fn repository_card(props: &Props) -> Html {
-    let Props { repository, desired_archive_state, on_checkbox_change } 
+    let Props { repo_key, repository, desired_archive_state, decided_by, mode, disabled, on_checkbox_change } 
             = props;
 
-    // If we pass this assertion, then the desired_archive_state.unwrap() in the HTML
-    // should be safe.
-    if desired_archive_state.is_none() {
-        assert!(repository.archived);
-    }
-
     let onclick: Callback<MouseEvent> = {
-        let id = repository.id;
+        let key = repo_key.clone();
         let on_checkbox_change = on_checkbox_change.clone();
 
         Callback::from(move |mouse_event: MouseEvent| {
@@ -40,7 +64,7 @@ pub fn repository_card(props: &Props) -> Html {
             web_sys::console::log_1(&format!("In ME callback desired state is {desired_archive_state}.").into());
 
             on_checkbox_change.emit(DesiredArchiveState {
-                id,
+                key: key.clone(),
                 desired_archive_state
             });
         })
@@ -49,25 +73,35 @@ pub fn repository_card(props: &Props) -> Html {
     html! {
         <div class="card card-compact">
             <div class="card-body">
-                if repository.archived {
-                    <p class="italic">{ "This repository is already archived" }</p>
-                } else {
+                if mode.can_select(repository) {
                     <div class="card-actions">
                         <div class="form-control">
                             <label class="label cursor-pointer">
                                 <input type="checkbox" 
-                                       checked={ #[allow(clippy::unwrap_used)] desired_archive_state.unwrap() } 
+                                       checked={ desired_archive_state.unwrap_or(false) } 
+                                       disabled={ *disabled }
                                        class="checkbox" {onclick} />
-                                <p class="label-text italic ml-2">{ "Archive this repository" }</p> 
+                                <p class="label-text italic ml-2">{
+                                    match mode {
+                                        SelectionMode::Archive => "Archive this repository",
+                                        SelectionMode::Unarchive => "Unarchive this repository",
+                                    }
+                                }</p> 
                             </label>
                         </div>
                     </div>
-                }
-                if repository.archived {
-                    <h2 class="card-title text-gray-300">{ &repository.name }</h2>
                 } else {
-                    <h2 class="card-title">{ &repository.name }</h2>
+                    <p class="italic">{
+                        if repository.archived { "This repository is already archived" } else { "This repository isn't archived" }
+                    }</p>
                 }
+                if let Some(rule) = decided_by {
+                    <p class="text-sm text-purple-700">{ format!("Decided by policy {rule}") }</p>
+                }
+                <h2 class={ if mode.can_select(repository) { "card-title" } else { "card-title text-gray-300" } }>
+                    <a class="link link-hover" href={ repository.html_url.clone() } target="_blank">{ &repository.name }</a>
+                </h2>
+                { badges(repository) }
                 {
                     repository.description.as_ref().map_or_else(
                         || html! { <p class="text-blue-700">{ "There was no description for this repository "}</p> },
@@ -75,7 +109,15 @@ pub fn repository_card(props: &Props) -> Html {
                     )
                 }
                 <p>{ format!("Last updated on {}; ", repository.updated_at.format("%Y-%m-%d")) }
-                   { format!("last pushed to on {}", repository.pushed_at.format("%Y-%m-%d")) }</p>
+                   { format!("last pushed to on {} ({})", repository.pushed_at.format("%Y-%m-%d"),
+                        describe_age(repository.pushed_at.with_timezone(&Utc), Utc::now())) }</p>
+                <p class="text-sm text-gray-500">{
+                    format!("{} KB; default branch {}; {} open issues and pull requests",
+                        repository.size, repository.default_branch, repository.open_issues_count)
+                }</p>
+                if !repository.can_archive() {
+                    <p class="text-sm text-orange-700">{ "You don't have admin access to this repository, so you won't be able to archive or unarchive it." }</p>
+                }
             </div>
         </div>
     }
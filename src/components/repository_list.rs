@@ -2,7 +2,8 @@ use gloo::console::log;
 use yew::prelude::*;
 use yewdux::prelude::use_store;
 
-use crate::repository::{RepoId, DesiredArchiveState, DesiredStateMap};
+use crate::policy::ArchivePolicy;
+use crate::repository::{RepoKey, DesiredArchiveState, DesiredStateMap, SelectionMode};
 use crate::components::repository_card::RepositoryCard;
 
 // TODO: Can we use `AttrValue` instead of `String` here?
@@ -11,29 +12,40 @@ use crate::components::repository_card::RepositoryCard;
 // https://yew.rs/docs/concepts/components/properties#memoryspeed-overhead-of-using-properties
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
-    pub repo_ids: Option<Vec<RepoId>>,
+    pub repo_keys: Option<Vec<RepoKey>>,
     pub empty_repo_list_message: String,
+    /// Whether to stop the checkboxes from being changed, e.g., while we're
+    /// sending GitHub requests for the repositories.
+    #[prop_or_default]
+    pub disabled: bool,
     pub on_checkbox_change: Callback<DesiredArchiveState>
 }
 
 #[function_component(RepositoryList)]
 pub fn repository_list(props: &Props) -> Html {
-    let Props { repo_ids, 
+    let Props { repo_keys, 
                 empty_repo_list_message, 
+                disabled,
                 on_checkbox_change } = props;
 
     let (state_map, _) = use_store::<DesiredStateMap>();
+    let (mode, _) = use_store::<SelectionMode>();
+    let (policy, _) = use_store::<ArchivePolicy>();
 
-    log!(format!("We're in repo list with repo IDs {repo_ids:?}"));
+    log!(format!("We're in repo list with repo keys {repo_keys:?}"));
     log!(format!("We're in repo list with ArchiveStateMap {state_map:?}"));
 
     #[allow(clippy::option_if_let_else)]
-    if let Some(repo_ids) = repo_ids {
-        repo_ids.iter()
-                .map(|repo_id: &RepoId| {
+    if let Some(repo_keys) = repo_keys {
+        repo_keys.iter()
+                .map(|repo_key: &RepoKey| {
             html! {
-                <RepositoryCard repository={ state_map.get_repo(*repo_id).clone() } 
-                                desired_archive_state={ state_map.get_desired_state(*repo_id) } 
+                <RepositoryCard repo_key={ repo_key.clone() }
+                                repository={ state_map.get_repo(repo_key).clone() } 
+                                desired_archive_state={ state_map.get_desired_state(repo_key) } 
+                                decided_by={ state_map.map.get(repo_key).and_then(|(_, state)| policy.decided_by(repo_key, *state)) }
+                                mode={ *mode }
+                                disabled={ *disabled }
                                 {on_checkbox_change} />
             }
         }).collect()
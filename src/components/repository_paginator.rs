@@ -1,90 +1,45 @@
-use std::num::ParseIntError;
-use std::ops::Deref;
-
-use url::{Url, ParseError};
+use std::collections::BTreeMap;
 
+use chrono::Local;
 use gloo::console::log;
-
-use reqwasm::http::{Request};
+use gloo::timers::callback::Timeout;
 
 use yew_router::prelude::*;
 use yew::prelude::*;
 use yewdux::prelude::{use_store, Dispatch};
-use yewdux::store::Store;
 
 use crate::Route;
-use crate::repository::{Repository, DesiredArchiveState, DesiredStateMap, DesiredState};
-use crate::page_repo_map::{PageRepoMap, PageNumber};
+use crate::repository::{DesiredArchiveState, DesiredStateMap, DesiredState, Organizations, Phase, RepoKey, SelectionMode};
+use crate::inactivity::InactivityRules;
+use crate::name_filter::NameFilter;
+use crate::page_repo_map::{PageRepoMap, PageRepoMaps, PageNumber};
+use crate::policy::ArchivePolicy;
+use crate::services::github_api::{ApiError, GitHubApiHandle, ListingBackend, RepoPage, RepoSource};
+use crate::components::api_error_view::ApiErrorView;
+use crate::components::assignment_groups::AssignmentGroups;
+use crate::components::github_api_provider::use_github_api;
+use crate::components::inactivity_rule_bar::InactivityRuleBar;
+use crate::components::name_filter_bar::NameFilterBar;
+use crate::components::plan_transfer::PlanTransfer;
+use crate::components::policy_loader::PolicyLoader;
 use crate::components::repository_list::RepositoryList;
+use crate::components::repository_search::RepositorySearch;
+use crate::components::selection_mode_toggle::SelectionModeToggle;
+use crate::components::undo_redo_toolbar::UndoRedoToolbar;
 
 #[derive(Debug, Clone, PartialEq, Eq, Properties)]
 pub struct Props {
     pub organization: String
 }
-#[derive(Debug, Clone)]
-struct State {
-    current_page: PageNumber,
-    last_page: PageNumber
-}
-
-#[derive(Debug)]
-enum LinkParseError {
-    InvalidUrl(ParseError),
-    PageEntryMissing(Url),
-    InvalidPageNumber(ParseIntError)
-}
-
-impl From<ParseError> for LinkParseError {
-    fn from(e: ParseError) -> Self {
-        Self::InvalidUrl(e)
-    }
-}
-
-impl From<ParseIntError> for LinkParseError {
-    fn from(e: ParseIntError) -> Self {
-        Self::InvalidPageNumber(e)
-    }
-}
-
-/*
- * This parses the `last` component of the link field in the response header from
- * GitHub, which tells us how many pages there are.
- * 
- * The link field looks like:
- * 
- * <https://api.github.com/organizations/18425666/repos?page=1&per_page=5>; rel="prev", <https://api.github.com/organizations/18425666/repos?page=3&per_page=5>; rel="next", <https://api.github.com/organizations/18425666/repos?page=5&per_page=5>; rel="last", <https://api.github.com/organizations/18425666/repos?page=1&per_page=5>; rel="first"
- */
-fn parse_last_page(link_str: &str) -> Result<Option<PageNumber>, LinkParseError> {
-    // This split won't do the desired thing if there can ever be a comma in a
-    // URL, but that doesn't seem likely given the structure of these GitHub URLs.
-    let last_entry = link_str
-        .split(", ")
-        .find_map(|s| s.trim().strip_suffix(r#"; rel="last""#));
-    // rel="last" is missing if we're on the last page
-    let last_entry = match last_entry {
-        None => return Ok(None),
-        Some(s) => s
-    };
-    // This fails and returns a LinkParseError::UrlParseError if we can't parse the URL.
-    let last_url = last_entry.trim_start_matches('<')
-        .trim_end_matches('>')
-        .parse::<Url>()?;
-    let num_pages_str = last_url.query_pairs()
-        // This returns the None variant if there was no "page" query parameter.
-        // This is an error on GitHub's part (or a major change to their API),
-        // and we'll return a LinkParseError::PageEntryMissingError if it happens.
-        .find(|(k, _)| k.eq("page"))
-        .map(|(_, v)| v)
-        .ok_or_else(|| LinkParseError::PageEntryMissing(last_url.clone()))?;
-    // This fails and returns a LinkParseError::PageNumberParseError if for some
-    // reason the `num_pages_str` can't be parsed to a `PageNumber`. This would also
-    // presumably be an error or major API change on the part of GitHub.
-    Ok(Some(num_pages_str.parse::<PageNumber>()?))
-}
 
 // The GitHub default is 30; they allow no more than 100.
 const REPOS_PER_PAGE: u8 = 30;
 
+// Once we know how many pages there are, we load the rest in the background,
+// one at a time and no more than one a second, so as not to eat into the
+// rate limit (or GitHub's patience) too quickly.
+const PREFETCH_INTERVAL_MS: u32 = 1_000;
+
 fn prev_button_class(current_page: PageNumber) -> String {
     let mut class = "btn btn-primary".to_string();
     if current_page == 1 {
@@ -101,78 +56,77 @@ fn next_button_class(loaded: bool) -> String {
     class
 }
 
-fn make_button_callback(page_number: PageNumber, repository_paginator_state: UseStateHandle<State>) -> Callback<MouseEvent> {
+fn make_button_callback(page_number: PageNumber, current_page: UseStateHandle<PageNumber>) -> Callback<MouseEvent> {
     Callback::from(move |_| {
-        let repo_state = State {
-            current_page: page_number,
-            last_page: repository_paginator_state.last_page
-        };
         web_sys::console::log_1(&format!("make_button_callback called with page number {page_number}.").into());
-        web_sys::console::log_1(&format!("New state is {repo_state:?}.").into());
-        repository_paginator_state.set(repo_state);
+        current_page.set(page_number);
     })
 }
 
-fn try_extract(link_str: &str, current_page: PageNumber) -> Result<PageNumber, LinkParseError> {
-    let parse_result = parse_last_page(link_str)?
-        .unwrap_or(current_page);
-    Ok(parse_result)
-}
-
-fn handle_parse_error(err: &LinkParseError) {
-    #[allow(clippy::unwrap_used)]
-    web_sys::window()
-        .unwrap()
-        .alert_with_message("There was an error contacting the GitHub server; please try again")
-        .unwrap();
+// Rather than popping up an alert, we hang on to the error so the paginator
+// can show it inline (with a way to try again) and stay usable.
+fn handle_api_error(err: ApiError, error: &UseStateHandle<Option<ApiError>>) {
     web_sys::console::error_1(
-        &format!("There was an error parsing the link field in the HTTP response: {:?}", err).into());
+        &format!("There was an error loading repositories from GitHub: {err}").into());
+    error.set(Some(err));
 }
 
-fn load_new_page(organization: &str, desired_state_map_dispatch: Dispatch<DesiredStateMap>, page_map: UseStateHandle<PageRepoMap>, current_page: PageNumber, state: UseStateHandle<State>) {
+fn load_new_page(api: GitHubApiHandle, organization: &str, source: RepoSource, backend: ListingBackend, desired_state_map_dispatch: Dispatch<DesiredStateMap>, current_page: PageNumber, error: UseStateHandle<Option<ApiError>>) {
     let organization = organization.to_owned();
     // TODO: Possibly change `spawn_local` to `use_async`.
     wasm_bindgen_futures::spawn_local(async move {
         web_sys::console::log_1(&format!("spawn_local called with organization {organization}.").into());
-        let request_url = format!("/orgs/{organization}/repos?sort=pushed&direction=asc&per_page={REPOS_PER_PAGE}&page={current_page}");
-        let response = Request::get(&request_url).send().await.unwrap();
-        let link = response.headers().get("link");
-        web_sys::console::log_1(&format!("The link element of the header was <{link:?}>.").into());
-        let last_page = match link.as_deref() {
-            None => 1,
-            Some(link_str) => match try_extract(link_str, current_page) {
-                Ok(last_page) => last_page,
-                Err(err) => { handle_parse_error(&err); return }
-            }
+        let RepoPage { repos: repos_result, last_page } = match api.list_repos(&source, current_page, REPOS_PER_PAGE).await {
+            Ok(repo_page) => repo_page,
+            Err(err) => { handle_api_error(err, &error); return }
         };
-        // TODO: This seems fairly slow when there are a lot of repositories. My guess
-        // is that parsing the huge pile of JSON we get back is at least part of the
-        // problem. Switching to GraphQL would potentially help this by allowing us to
-        // specify the exact info we need for each repository (which is a tiny subset of
-        // what GitHub currently provides), which should greatly reduce the
-        // size of the JSON package and the cost of the parsing.
-        let repos_result: Vec<Repository> = response.json().await.unwrap();
-        
+        error.set(None);
+
+        // The user may have switched backends or sources (or stopped working on
+        // this organization) while we were waiting, or this page may have already
+        // arrived from an earlier request.
+        let page_maps = Dispatch::<PageRepoMaps>::new().get();
+        if !page_maps.is_for(&organization, &source, backend)
+            || page_maps.get(&organization).is_some_and(|page_map| page_map.has_loaded_page(current_page)) {
+            return
+        }
+
+        // The new repositories start out the way the archive policy would leave
+        // them or, failing that, the inactivity rule. Only the ones we haven't
+        // seen before are decided, since `with_repos` leaves the rest alone.
+        let now = Local::now();
+        let mut decided = BTreeMap::new();
+        if Dispatch::<ArchivePolicy>::new().get().is_loaded() {
+            let known = desired_state_map_dispatch.get();
+            Dispatch::<ArchivePolicy>::new().reduce_mut(|policy| {
+                for repo in &repos_result {
+                    let key = RepoKey::new(&organization, repo.id);
+                    if known.map.contains_key(&key) {
+                        continue
+                    }
+                    if let Some(selected) = policy.decide_new(&key, repo, now) {
+                        decided.insert(repo.id, selected);
+                    }
+                }
+            });
+        }
+        let rules = Dispatch::<InactivityRules>::new().get();
+        let rule = rules.get(&organization);
         desired_state_map_dispatch.reduce_mut(|desired_state_map| {
-            desired_state_map.with_repos(&repos_result);
+            desired_state_map.with_repos_selected_by(&organization, &repos_result, |repo| {
+                decided.get(&repo.id).copied().unwrap_or_else(|| rule.is_none_or(|rule| rule.is_inactive(repo, now)))
+            });
         });
 
-        let mut new_page_map 
-            = page_map
-                .deref()
-                .clone();
-        new_page_map.add_page(
-            current_page,
-            repos_result.iter().map(|r| r.id).collect()
-        );
-        page_map.set(new_page_map);
-
-        let repo_state = State {
-            current_page,
-            last_page
-        };
-        web_sys::console::log_1(&format!("The new repo state is <{repo_state:?}>.").into());
-        state.set(repo_state);
+        Dispatch::<PageRepoMaps>::new().reduce_mut(|page_maps| {
+            if let Some(page_map) = page_maps.get_mut(&organization) {
+                page_map.add_page(
+                    current_page,
+                    repos_result.iter().map(|r| r.id).collect()
+                );
+                page_map.set_last_page(last_page);
+            }
+        });
     });
 }
 
@@ -192,20 +146,32 @@ fn load_new_page(organization: &str, desired_state_map_dispatch: Dispatch<Desire
 #[function_component(RepositoryPaginator)]
 pub fn repository_paginator(props: &Props) -> Html {
     let Props { organization } = props;
-    let page_map = use_state(|| PageRepoMap::new());
-
-    let repository_paginator_state = use_state(|| State {
-        current_page: 1,
-        last_page: 0
-    });
-    // TODO: I feel like these should actually be two separate state objects. Instead
-    //   of having one big state object, it's probably better to separate out the state
-    //   elements so they can be handled separately.
-    // TODO: In doing this separation, we should change `last_page` to be `Option<usize>`
-    //   so we'd have a clean way of distinguishing between when it's been set and when
-    //   it hasn't.
-    let State { current_page, last_page }
-        = (*repository_paginator_state).clone();
+    let api = use_github_api();
+    let (page_maps, page_maps_dispatch) = use_store::<PageRepoMaps>();
+    let page_map = page_maps.get(organization).cloned().unwrap_or_default();
+    let error = use_state(|| None::<ApiError>);
+    // Kept apart from `error` so a background failure doesn't look like a
+    // failure to load the page being shown.
+    let prefetch_error = use_state(|| None::<ApiError>);
+    // Usually we start at the first page, unless we were sent here to see a
+    // particular one (e.g., by a search result in another organization).
+    let requested_page = page_maps.requested_page(organization);
+    let current_page_handle = use_state(|| requested_page.unwrap_or(1));
+    {
+        let organization = organization.clone();
+        use_effect_with_deps(
+            move |()| {
+                if requested_page.is_some() {
+                    Dispatch::<PageRepoMaps>::new().reduce_mut(|page_maps| page_maps.clear_requested_page(&organization));
+                }
+                || ()
+            },
+            ()
+        );
+    }
+    let current_page = *current_page_handle;
+    // This is 0 until the first page has loaded and we know how many there are.
+    let last_page = page_map.last_page().unwrap_or(0);
 
     log!(format!("In paginator with page_map {page_map:?}."));
 
@@ -213,71 +179,188 @@ pub fn repository_paginator(props: &Props) -> Html {
     //   the paginator, or use Yew's context tools to share this with the review and submit
     //   component.
     let (desired_state_map, desired_state_map_dispatch) = use_store::<DesiredStateMap>();
+    let (backend, _) = use_store::<ListingBackend>();
+    let backend = *backend;
+    let (organizations, _) = use_store::<Organizations>();
+    let source = organizations.source(organization);
+    // Start over on the pages whenever the organization or its source changes, or
+    // when we switch backends, since the two backends don't agree on what's on each page. If we
+    // already have pages for this organization (e.g., because we've been looking at
+    // another one, or just resumed a saved session), we keep them. Selections are
+    // kept either way, since they don't depend on which page a repository is on.
     {
-        let repository_paginator_state = repository_paginator_state.clone();
-        let page_map = page_map.clone();
-        let organization = organization.clone();
-        let desired_state_map_dispatch = desired_state_map_dispatch.clone();
+        let current_page_handle = current_page_handle.clone();
+        let error = error.clone();
         use_effect_with_deps(
-            move |_| {
-                repository_paginator_state.set(State { current_page: 1, last_page: 0 });
-                page_map.set(PageRepoMap::new());
-                desired_state_map_dispatch.set(DesiredStateMap::new());
+            move |(organization, source, backend)| {
+                if !page_maps_dispatch.get().is_for(organization, source, *backend) {
+                    current_page_handle.set(1);
+                    error.set(None);
+                    page_maps_dispatch.reduce_mut(|page_maps| page_maps.insert(organization, PageRepoMap::for_source(source.clone(), *backend)));
+                }
                 || ()
             },
-            organization
-        )
+            (organization.clone(), source.clone(), backend)
+        );
     }
 
     web_sys::console::log_1(&format!("RepositoryPaginator called with organization {:?}.", organization).into());
     web_sys::console::log_1(&format!("Current StateMap is {:?}.", desired_state_map).into());
 
-    // TODO: We want to see if the current page has already been loaded, and only do
-    // `update_state_for_organization` if it has not been loaded yet. Might make sense
-    // to fix this along with switching to "Prev"/"Next" UI model.
     // TODO: It's possible that this would all be easier if we used a structural component
     //   here with messages for the various updates instead of having multiple `use_effect_with_deps`
     //   calls.
     {
         let organization = organization.clone();
-        let page_map = page_map.clone();
-        let repository_paginator_state = repository_paginator_state.clone();
+        let source = source.clone();
         let desired_state_map_dispatch = desired_state_map_dispatch.clone();
+        let api = api.clone();
+        let error = error.clone();
         use_effect_with_deps(
             move |(page_map, current_page)| {
                 log!(format!("Organization = {organization} and current page = {current_page}."));
                 log!(format!("Current page has loaded = {}", page_map.has_loaded_page(*current_page)));
                 let current_page = *current_page;
-                if !page_map.has_loaded_page(current_page) {
-                    load_new_page(&organization.clone(), 
+                if page_map.is_for(&source, backend) && !page_map.has_loaded_page(current_page) {
+                    load_new_page(api,
+                        &organization,
+                        source,
+                        backend,
                         desired_state_map_dispatch, 
-                        page_map.clone(),
                         current_page, 
-                        repository_paginator_state);
+                        error);
                 }
                 || ()
             }, 
-            (page_map, current_page)
+            (page_map.clone(), current_page)
         );
     }
     
+    // Load the rest of the pages in the background, starting with the first one
+    // we're missing. Each page that arrives changes `page_map`, which schedules the
+    // next one, so there's only ever one in flight. We stop if one fails, until
+    // the user asks us to try again.
+    {
+        let prefetch_failed = prefetch_error.is_some();
+        let organization = organization.clone();
+        let source = source.clone();
+        let desired_state_map_dispatch = desired_state_map_dispatch.clone();
+        let api = api.clone();
+        let prefetch_error = prefetch_error.clone();
+        use_effect_with_deps(
+            move |(page_map, failed)| {
+                let timeout = page_map.first_missing_page()
+                    .filter(|_| !*failed && page_map.is_for(&source, backend))
+                    .map(|page_number| Timeout::new(PREFETCH_INTERVAL_MS, move || {
+                        load_new_page(api, &organization, source, backend, desired_state_map_dispatch, page_number, prefetch_error);
+                    }));
+                // Dropping the timeout cancels it, e.g., if the page arrived some other way first.
+                move || drop(timeout)
+            },
+            (page_map.clone(), prefetch_failed)
+        );
+    }
+
+    let on_prefetch_retry: Callback<MouseEvent> = {
+        let prefetch_error = prefetch_error.clone();
+        Callback::from(move |_| prefetch_error.set(None))
+    };
+
+    let on_retry: Callback<MouseEvent> = {
+        let organization = organization.clone();
+        let source = source.clone();
+        let desired_state_map_dispatch = desired_state_map_dispatch.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            error.set(None);
+            load_new_page(api.clone(),
+                &organization,
+                source.clone(),
+                backend,
+                desired_state_map_dispatch.clone(),
+                current_page,
+                error.clone());
+        })
+    };
+
+    let (mode, _) = use_store::<SelectionMode>();
+
+    // Select (or deselect) everything on this page that can be selected in
+    // this mode, as a single undoable change.
+    let page_keys: Option<Vec<RepoKey>> = page_map.get_repo_ids(current_page)
+        .map(|ids| ids.into_iter().map(|id| RepoKey::new(organization, id)).collect());
+    let select_page = |selected: bool| -> Callback<MouseEvent> {
+        let mode = *mode;
+        let repo_keys = page_keys.clone().unwrap_or_default();
+        let desired_state_map_dispatch = desired_state_map_dispatch.clone();
+        Callback::from(move |_| {
+            desired_state_map_dispatch.reduce_mut(|state_map| {
+                let changes: Vec<_> = repo_keys.iter()
+                    .filter(|key| mode.can_select(state_map.get_repo(key)))
+                    .map(|key| (key.clone(), DesiredState::from_paginator_state(selected, mode)))
+                    .collect();
+                for err in state_map.transition_all(changes, Phase::Paginator) {
+                    web_sys::console::error_1(&format!("Skipping a repository: {err}").into());
+                }
+            });
+        })
+    };
+    let on_select_page = select_page(true);
+    let on_deselect_page = select_page(false);
+
+    // While there's a filter, show every loaded repository that matches it
+    // rather than just the ones on this page.
+    let filter = use_state(NameFilter::default);
+    let on_filter_change: Callback<NameFilter> = {
+        let filter = filter.clone();
+        Callback::from(move |new_filter| filter.set(new_filter))
+    };
+    let matching: Option<Vec<RepoKey>> = match filter.matcher() {
+        Ok(matcher) if filter.is_active() => Some(desired_state_map.get_repos_in(organization)
+            .filter(|(_, repo)| matcher.matches(&repo.name))
+            .map(|(key, _)| key.clone())
+            .collect()),
+        _ => None
+    };
+    let (shown_keys, empty_message) = matching.as_ref().map_or_else(
+        || (page_keys.clone(), "Loading..."),
+        |matching| ((!matching.is_empty()).then(|| matching.clone()), "None of the repositories loaded so far match the filter"));
+
+    // The assignment view groups every loaded repository (or every one that
+    // matches the filter), whatever page it's on.
+    let by_assignment = use_state(|| false);
+    let show_view = |grouped: bool| -> Callback<MouseEvent> {
+        let by_assignment = by_assignment.clone();
+        Callback::from(move |_| by_assignment.set(grouped))
+    };
+    let grouped_keys: Vec<RepoKey> = matching.unwrap_or_else(||
+        desired_state_map.get_repos_in(organization).map(|(key, _)| key.clone()).collect());
+
     let on_checkbox_change: Callback<DesiredArchiveState> = {
+        let mode = *mode;
         Callback::from(move |desired_archive_state| {
-            let DesiredArchiveState { id, desired_archive_state } = desired_archive_state;
+            let DesiredArchiveState { key, desired_archive_state } = desired_archive_state;
             desired_state_map_dispatch.reduce_mut(|state_map| {
-                state_map.update_desired_state(id, DesiredState::from_paginator_state(desired_archive_state));
+                if let Err(err) = state_map.transition(&key, DesiredState::from_paginator_state(desired_archive_state, mode), Phase::Paginator) {
+                    web_sys::console::error_1(&format!("Ignoring checkbox change: {err}").into());
+                }
             });
         })
     };
 
+    let on_go_to_page: Callback<PageNumber> = {
+        let current_page_handle = current_page_handle.clone();
+        Callback::from(move |page_number| current_page_handle.set(page_number))
+    };
+
     let prev: Callback<MouseEvent> = {
         // assert!(current_page > 1);
-        make_button_callback(current_page-1, repository_paginator_state.clone())
+        make_button_callback(current_page-1, current_page_handle.clone())
     };
 
     let next_or_review: Callback<MouseEvent> = {
         if current_page < last_page {
-            make_button_callback(current_page+1, repository_paginator_state)
+            make_button_callback(current_page+1, current_page_handle)
         } else {
             let history = use_history().unwrap();
             Callback::from(move |_: MouseEvent| history.push(Route::ReviewAndSubmit))
@@ -286,9 +369,43 @@ pub fn repository_paginator(props: &Props) -> Html {
     
     html! {
         <>
-            <RepositoryList repo_ids={page_map.get_repo_ids(current_page)}
-                            empty_repo_list_message={ "Loading..." }
-                            {on_checkbox_change} />
+            <SelectionModeToggle />
+            <div class="flex gap-2">
+                <UndoRedoToolbar />
+                <div class="btn-group">
+                    <button class="btn btn-sm" onclick={ on_select_page }>{ "Select all on this page" }</button>
+                    <button class="btn btn-sm" onclick={ on_deselect_page }>{ "Deselect all on this page" }</button>
+                </div>
+            </div>
+            <PlanTransfer />
+            <PolicyLoader />
+            if let Some(err) = (*error).clone() {
+                <ApiErrorView error={ err }
+                              action={ format!("load page {current_page} of the repositories for {}", source.describe()) }
+                              {on_retry} />
+            }
+            <InactivityRuleBar organization={ organization.clone() } />
+            <RepositorySearch organization={ organization.clone() } {on_go_to_page} on_checkbox_change={ on_checkbox_change.clone() } />
+            <NameFilterBar organization={ organization.clone() } filter={ (*filter).clone() } on_change={ on_filter_change } />
+            <div class="tabs">
+                <a class={ if *by_assignment { "tab tab-bordered" } else { "tab tab-bordered tab-active" } } onclick={ show_view(false) }>{ "By page" }</a>
+                <a class={ if *by_assignment { "tab tab-bordered tab-active" } else { "tab tab-bordered" } } onclick={ show_view(true) }>{ "By assignment" }</a>
+            </div>
+            if *by_assignment {
+                <AssignmentGroups repo_keys={ grouped_keys } {on_checkbox_change} />
+            } else {
+                <RepositoryList repo_keys={ shown_keys }
+                                empty_repo_list_message={ empty_message }
+                                {on_checkbox_change} />
+            }
+            if let Some(err) = (*prefetch_error).clone() {
+                <ApiErrorView error={ err }
+                              action={ format!("load the rest of the repositories for {} in the background", source.describe()) }
+                              on_retry={ on_prefetch_retry } />
+            }
+            if last_page > 0 {
+                <p class="text-sm text-gray-500">{ format!("{} of {last_page} pages loaded", page_map.loaded_page_count()) }</p>
+            }
             <div class="btn-group">
                 <button class={ prev_button_class(current_page) } onclick={prev}>
                     { "Prev" }
@@ -0,0 +1,105 @@
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::page_repo_map::{PageNumber, PageRepoMaps};
+use crate::repository::{DesiredArchiveState, DesiredStateMap, Organizations, RepoKey, SelectionMode};
+use crate::search::{is_query, search};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    /// The organization the paginator is showing.
+    pub organization: String,
+    pub on_go_to_page: Callback<PageNumber>,
+    pub on_checkbox_change: Callback<DesiredArchiveState>
+}
+
+// Going to a page in another organization means switching to it, and asking
+// its paginator to start at that page.
+fn go_to_page(key: &RepoKey, page_number: PageNumber, organization: &str, on_go_to_page: &Callback<PageNumber>) -> Callback<MouseEvent> {
+    let on_go_to_page = on_go_to_page.clone();
+    let target = key.organization.clone();
+    let same_organization = target == organization;
+    Callback::from(move |_| {
+        if same_organization {
+            on_go_to_page.emit(page_number);
+        } else {
+            Dispatch::<PageRepoMaps>::new().reduce_mut(|page_maps| page_maps.request_page(&target, page_number));
+            Dispatch::<Organizations>::new().reduce_mut(|organizations| { organizations.add(&target); });
+        }
+    })
+}
+
+fn result_view(key: &RepoKey, state_map: &DesiredStateMap, page_maps: &PageRepoMaps, mode: SelectionMode, props: &Props) -> Html {
+    let Props { organization, on_go_to_page, on_checkbox_change } = props;
+    let repo = state_map.get_repo(key);
+    let onclick: Callback<MouseEvent> = {
+        let key = key.clone();
+        let on_checkbox_change = on_checkbox_change.clone();
+        Callback::from(move |mouse_event: MouseEvent| {
+            let target: HtmlInputElement = mouse_event.target().unwrap_throw().dyn_into().unwrap_throw();
+            on_checkbox_change.emit(DesiredArchiveState { key: key.clone(), desired_archive_state: target.checked() });
+        })
+    };
+    let unselectable = !mode.can_select(repo);
+    let page = page_maps.get(&key.organization).and_then(|page_map| page_map.page_of(key.id));
+
+    html! {
+        <li class="flex flex-wrap gap-2 items-center">
+            <input type="checkbox" class="checkbox checkbox-sm"
+                   checked={ state_map.get_desired_state(key).unwrap_or(false) }
+                   disabled={ unselectable } {onclick} />
+            <a class="link link-hover font-semibold" href={ repo.html_url.clone() } target="_blank">{ &repo.name }</a>
+            if let Some(description) = &repo.description {
+                <span class="text-sm text-green-700">{ description }</span>
+            }
+            if let Some(page_number) = page {
+                <a class="link link-primary text-sm" onclick={ go_to_page(key, page_number, organization, on_go_to_page) }>{
+                    if key.organization == *organization { format!("page {page_number}") } else { format!("{}, page {page_number}", key.organization) }
+                }</a>
+            }
+        </li>
+    }
+}
+
+/// Search the name and description of every repository we've loaded, in
+/// every organization, with a checkbox for each result and a link to the
+/// page it's on.
+#[function_component(RepositorySearch)]
+pub fn repository_search(props: &Props) -> Html {
+    let (state_map, _) = use_store::<DesiredStateMap>();
+    let (page_maps, _) = use_store::<PageRepoMaps>();
+    let (mode, _) = use_store::<SelectionMode>();
+    let query = use_state(String::new);
+
+    let oninput = {
+        let query = query.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            query.set(input.value());
+        })
+    };
+
+    let results = search(&state_map, &query);
+
+    html! {
+        <div class="flex flex-col gap-1">
+            <input type="search" class="input input-bordered input-sm"
+                   placeholder="Search names and descriptions of every loaded repository"
+                   value={ (*query).clone() } {oninput} />
+            if is_query(&query) {
+                <p class="text-sm text-gray-500">{
+                    match results.len() {
+                        0 => "No loaded repositories match".to_owned(),
+                        1 => "1 match".to_owned(),
+                        n => format!("{n} matches"),
+                    }
+                }</p>
+                <ul class="flex flex-col gap-1">
+                    { for results.iter().map(|key| result_view(key, &state_map, &page_maps, *mode, props)) }
+                </ul>
+            }
+        </div>
+    }
+}
@@ -1,51 +1,228 @@
-use web_sys::MouseEvent;
-use yew::{function_component, html, Callback};
-use yewdux::prelude::use_store;
+use std::collections::BTreeMap;
 
-use crate::repository::{DesiredStateMap, DesiredArchiveState, DesiredState};
+use chrono::{DateTime, Utc};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{HtmlInputElement, MouseEvent};
+use yew::{function_component, html, use_reducer, use_state, Callback, Event, Html};
+use yewdux::prelude::{use_store, Dispatch};
+
+use crate::repository::{DesiredStateMap, DesiredArchiveState, DesiredState, Phase, RepoKey, SelectionMode, TransitionError};
+use crate::components::audit_log_view::AuditLogView;
+use crate::components::batch_progress::BatchProgressView;
+use crate::components::github_api_provider::use_github_api;
 use crate::components::repository_list::RepositoryList;
-use crate::services::archive_repos::archive_repositories;
+use crate::components::undo_redo_toolbar::UndoRedoToolbar;
+use crate::services::archive_repos::ArchiveResult;
+use crate::services::audit_log::{AuditBatch, AuditEntry, AuditLog};
+use crate::services::batch::{BatchEvent, BatchExecutor, BatchProgress, CancelHandle, DEFAULT_CONCURRENCY, MAX_CONCURRENCY};
+use crate::services::session::CurrentUser;
+
+/// Move the repository to whatever state GitHub's response puts it in.
+fn record_result(result: &ArchiveResult) -> Result<(), TransitionError> {
+    let mut recorded = Ok(());
+    Dispatch::<DesiredStateMap>::new().reduce_mut(|archive_state_map| {
+        recorded = archive_state_map.transition(&result.key, result.outcome.desired_state(), Phase::Submission).map(|_| ());
+    });
+    recorded
+}
+
+/// Add what happened to a repository to the audit log for the batch that started at `started_at`.
+fn audit(mode: SelectionMode, started_at: DateTime<Utc>, event: &BatchEvent) {
+    match event {
+        BatchEvent::Done(result) | BatchEvent::Failed(result) =>
+            AuditLog::append(started_at, AuditEntry::from_result(mode, result, Utc::now())),
+        BatchEvent::Cancelled(key) => {
+            let state_map = Dispatch::<DesiredStateMap>::new().get();
+            if let Some((repo, state)) = state_map.map.get(key) {
+                AuditLog::append(started_at, AuditEntry::cancelled(key, repo.name.clone(), *state, Utc::now()));
+            }
+        },
+        BatchEvent::Finished => AuditLog::finish_batch(started_at, Utc::now()),
+        BatchEvent::Started | BatchEvent::Queued { .. } | BatchEvent::InFlight(_) | BatchEvent::NotRecorded { .. } => ()
+    }
+}
+
+/// One list per organization, so it's clear which organization each
+/// repository will be changed in.
+fn review_lists(keys: Vec<RepoKey>, mode: SelectionMode, disabled: bool, on_checkbox_change: &Callback<DesiredArchiveState>) -> Html {
+    if keys.is_empty() {
+        let message = match mode {
+            SelectionMode::Archive => "You selected no repositories to archive",
+            SelectionMode::Unarchive => "You selected no repositories to unarchive",
+        };
+        return html! { <p>{ message }</p> }
+    }
+    let mut by_organization: BTreeMap<String, Vec<RepoKey>> = BTreeMap::new();
+    for key in keys {
+        by_organization.entry(key.organization.clone()).or_default().push(key);
+    }
+    by_organization.into_iter().map(|(organization, keys)| html! {
+        <div class="mt-4">
+            <h2 class="text-2xl font-semibold">{ format!("{organization} ({})", keys.len()) }</h2>
+            <RepositoryList repo_keys={ Some(keys) }
+                            empty_repo_list_message={ String::new() }
+                            {disabled}
+                            on_checkbox_change={ on_checkbox_change.clone() } />
+        </div>
+    }).collect()
+}
 
 /// Review selected repositories to archive and
 /// submit archive requests.
 #[function_component(ReviewAndSubmit)]
 pub fn review_and_submit() -> Html {
-    let (archive_state_map, archive_state_dispatch) 
+    let (archive_state_map, archive_state_dispatch)
         = use_store::<DesiredStateMap>();
+    let (mode, _) = use_store::<SelectionMode>();
+    let mode = *mode;
+    let api = use_github_api();
+    let batch_progress = use_reducer(BatchProgress::default);
+    let cancel_handle = use_state(|| None::<CancelHandle>);
+    let concurrency = use_state(|| DEFAULT_CONCURRENCY);
+
+    // Select (or deselect) everything in the review as a single undoable change.
+    let select_all = |selected: bool| -> Callback<MouseEvent> {
+        let archive_state_dispatch = archive_state_dispatch.clone();
+        Callback::from(move |_| {
+            archive_state_dispatch.reduce_mut(|archive_state_map| {
+                let changes: Vec<_> = archive_state_map.get_repo_keys_to_review(mode)
+                    .into_iter()
+                    .map(|key| (key, DesiredState::from_review_state(selected, mode)))
+                    .collect();
+                for err in archive_state_map.transition_all(changes, Phase::Review) {
+                    web_sys::console::error_1(&format!("Skipping a repository: {err}").into());
+                }
+            });
+        })
+    };
+    let on_select_all = select_all(true);
+    let on_deselect_all = select_all(false);
 
     let on_checkbox_change: Callback<DesiredArchiveState> = {
         Callback::from(move |desired_archive_state| {
-            let DesiredArchiveState { id, desired_archive_state } = desired_archive_state;
+            let DesiredArchiveState { key, desired_archive_state } = desired_archive_state;
             archive_state_dispatch.reduce_mut(|archive_state_map| {
-                archive_state_map.update_desired_state(id, DesiredState::from_review_state(desired_archive_state));
+                if let Err(err) = archive_state_map.transition(&key, DesiredState::from_review_state(desired_archive_state, mode), Phase::Review) {
+                    web_sys::console::error_1(&format!("Ignoring checkbox change: {err}").into());
+                }
             });
         })
     };
 
+    let on_concurrency_change: Callback<Event> = {
+        let concurrency = concurrency.clone();
+        Callback::from(move |event: Event| {
+            let target: HtmlInputElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            if let Ok(value) = target.value().parse::<usize>() {
+                concurrency.set(value.clamp(1, MAX_CONCURRENCY));
+            }
+        })
+    };
+
     let onclick: Callback<MouseEvent> = {
         let archive_state_map = archive_state_map.clone();
+        let batch_progress = batch_progress.clone();
+        let cancel_handle = cancel_handle.clone();
+        let concurrency = *concurrency;
+        Callback::from(move |_| {
+            let repos = archive_state_map.get_owned_repos_to_change(mode);
+            // Don't fill the audit log with batches that didn't do anything.
+            if repos.is_empty() {
+                return
+            }
+            let mut organizations: Vec<String> = repos.iter().map(|(key, _)| key.organization.clone()).collect();
+            organizations.dedup();
+            let started_at = Utc::now();
+            AuditLog::start_batch(AuditBatch::new(
+                started_at,
+                Dispatch::<CurrentUser>::new().get().login.clone(),
+                organizations,
+                mode,
+                repos.iter().map(|(key, repo)| format!("{}/{}", key.organization, repo.name)).collect()
+            ));
+            let dispatcher = batch_progress.dispatcher();
+            let on_event = Callback::from(move |event: BatchEvent| {
+                let not_recorded = match &event {
+                    BatchEvent::Done(result) | BatchEvent::Failed(result) => record_result(result).err().map(|err| {
+                        web_sys::console::error_1(&format!("Unable to record the result for {}: {err}", result.name).into());
+                        BatchEvent::NotRecorded { key: result.key.clone(), message: err.to_string() }
+                    }),
+                    _ => None
+                };
+                audit(mode, started_at, &event);
+                dispatcher.dispatch(event);
+                if let Some(not_recorded) = not_recorded {
+                    dispatcher.dispatch(not_recorded);
+                }
+            });
+            let cancel = BatchExecutor::new(api.clone(), concurrency)
+                .with_mode(mode)
+                .run(repos, &on_event);
+            cancel_handle.set(Some(cancel));
+        })
+    };
+
+    let on_cancel: Callback<MouseEvent> = {
         Callback::from(move |_| {
-            archive_repositories(archive_state_map.get_repos_to_archive());
+            if let Some(cancel) = cancel_handle.as_ref() {
+                cancel.cancel();
+            }
         })
     };
 
     // TODO: We need some kind of shared header that comes across to pages like this.
     html! {
         <div>
-            <RepositoryList repo_ids={ archive_state_map.get_repo_ids_to_review() }
-                            empty_repo_list_message={ "You selected no repositories to archive" }
-                            { on_checkbox_change } />
+            <div class="flex gap-2">
+                <UndoRedoToolbar />
+                <div class="btn-group">
+                    <button class="btn btn-sm" disabled={ batch_progress.running } onclick={ on_select_all }>{ "Select all" }</button>
+                    <button class="btn btn-sm" disabled={ batch_progress.running } onclick={ on_deselect_all }>{ "Deselect all" }</button>
+                </div>
+            </div>
+            { review_lists(archive_state_map.get_repo_keys_to_review(mode), mode, batch_progress.running, &on_checkbox_change) }
 
             <p class="text-xl text-red-700">{
-                "Clicking the 'Archive selected repositories' button will send archive requests
-                 to GitHub for each of the selected repositories. This *cannot* be undone
-                 here in ice-repos, and un-archiving in the GitHub web interface is possible
-                 but tedious for large number of repositories. Use with caution."
+                match mode {
+                    SelectionMode::Archive =>
+                        "Clicking the 'Archive selected repositories' button will send archive requests
+                         to GitHub for each of the selected repositories. This *cannot* be undone
+                         here in ice-repos, and un-archiving in the GitHub web interface is possible
+                         but tedious for large number of repositories. Use with caution.",
+                    SelectionMode::Unarchive =>
+                        "Clicking the 'Unarchive selected repositories' button will send unarchive requests
+                         to GitHub for each of the selected repositories. They will become writable again,
+                         and anyone with access will be able to push to them, open issues, and so on.
+                         Make sure these are the repositories you mean to bring back."
+                }
             }</p>
 
             <div class="form-control mt-6">
-                <button type="submit" class="btn btn-primary" {onclick}>{ "Archive selected repositories" }</button>
+                <label class="label">
+                    <span class="label-text">{ "How many requests to send to GitHub at once" }</span>
+                </label>
+                <input type="number" min="1" max={ MAX_CONCURRENCY.to_string() } class="input input-bordered w-24"
+                       value={ concurrency.to_string() } onchange={ on_concurrency_change } />
             </div>
+
+            <div class="form-control mt-6">
+                if batch_progress.running {
+                    <button class="btn btn-warning" onclick={ on_cancel }>{ "Cancel" }</button>
+                } else {
+                    <button type="submit" class="btn btn-primary" {onclick}>{
+                        match mode {
+                            SelectionMode::Archive => "Archive selected repositories",
+                            SelectionMode::Unarchive => "Unarchive selected repositories",
+                        }
+                    }</button>
+                }
+            </div>
+
+            if batch_progress.total() > 0 {
+                <BatchProgressView progress={ (*batch_progress).clone() } />
+            }
+
+            <AuditLogView />
         </div>
     }
-}
\ No newline at end of file
+}
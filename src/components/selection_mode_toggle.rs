@@ -0,0 +1,31 @@
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::repository::SelectionMode;
+
+/// Switch between choosing repositories to archive and choosing
+/// already archived repositories to unarchive.
+#[function_component(SelectionModeToggle)]
+pub fn selection_mode_toggle() -> Html {
+    let (mode, dispatch) = use_store::<SelectionMode>();
+
+    let onclick: Callback<MouseEvent> = {
+        let mode = *mode;
+        Callback::from(move |_| {
+            dispatch.set(match mode {
+                SelectionMode::Archive => SelectionMode::Unarchive,
+                SelectionMode::Unarchive => SelectionMode::Archive,
+            });
+        })
+    };
+
+    html! {
+        <div class="form-control">
+            <label class="label cursor-pointer justify-start">
+                <input type="checkbox" class="toggle"
+                       checked={ *mode == SelectionMode::Unarchive } {onclick} />
+                <span class="label-text ml-2">{ "Unarchive mode: select archived repositories to restore" }</span>
+            </label>
+        </div>
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{Local, Utc};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::page_repo_map::PageRepoMaps;
+use crate::repository::{DesiredStateMap, Organizations, SelectionMode};
+use crate::services::github_api::{ListingBackend, RepoSource};
+use crate::services::session::{self, describe_age, CurrentUser, SavedSession, SessionStatus};
+use crate::components::github_api_provider::use_github_api;
+
+fn resume(saved: &SavedSession) {
+    // The page map has to be in place before the organization is added,
+    // or the paginator will think it's out of date and start over. Any other
+    // organizations we're working on are left as they are.
+    Dispatch::<ListingBackend>::new().set(saved.backend);
+    Dispatch::<SelectionMode>::new().set(saved.mode);
+    Dispatch::<DesiredStateMap>::new().reduce_mut(|state_map| {
+        state_map.remove_organization(&saved.organization).merge(saved.desired_state_map.clone());
+    });
+    Dispatch::<PageRepoMaps>::new().reduce_mut(|page_maps| page_maps.insert(&saved.organization, saved.page_repo_map.clone()));
+    let source = saved.page_repo_map.source().cloned().unwrap_or_else(|| RepoSource::Organization(saved.organization.clone()));
+    Dispatch::<Organizations>::new().reduce_mut(|organizations| { organizations.add_source(source); });
+    Dispatch::<SessionStatus>::new().set(SessionStatus::Active);
+}
+
+fn start_fresh(login: &str, saved: &SavedSession) {
+    session::discard(login, &saved.organization);
+    Dispatch::<SessionStatus>::new().set(SessionStatus::Active);
+}
+
+/// Save the selections and loaded pages for each organization as they change,
+/// and when there's an earlier session for the user (or for an organization
+/// they add), offer to resume it or start fresh.
+#[function_component(SessionManager)]
+pub fn session_manager() -> Html {
+    let api = use_github_api();
+    let (user, user_dispatch) = use_store::<CurrentUser>();
+    let (status, status_dispatch) = use_store::<SessionStatus>();
+    let (organizations, _) = use_store::<Organizations>();
+    let (desired_state_map, _) = use_store::<DesiredStateMap>();
+    let (page_maps, _) = use_store::<PageRepoMaps>();
+    let (mode, _) = use_store::<SelectionMode>();
+
+    // Find out who we are, and whether they left anything behind last time.
+    use_effect_with_deps(
+        move |()| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match api.current_user().await {
+                    Ok(current_user) => {
+                        let status = session::most_recent(&current_user.login)
+                            .map_or(SessionStatus::Active, |saved| SessionStatus::Offered(Box::new(saved)));
+                        user_dispatch.set(CurrentUser { login: Some(current_user.login) });
+                        status_dispatch.set(status);
+                    }
+                    // Without a login there's nowhere to save to, so we stay in
+                    // `Loading` and the app works as if sessions didn't exist.
+                    Err(err) => web_sys::console::warn_1(
+                        &format!("Unable to find the current user, so sessions won't be saved: {err}").into())
+                }
+            });
+            || ()
+        },
+        ()
+    );
+
+    // If the user adds an organization they've worked on before, offer that
+    // session.
+    {
+        let login = user.login.clone();
+        use_effect_with_deps(
+            move |organizations| {
+                if let (Some(login), Some(organization)) = (login, organizations.current.as_deref()) {
+                    let status = Dispatch::<SessionStatus>::new().get();
+                    let already_loaded = Dispatch::<PageRepoMaps>::new().get().get(organization).is_some();
+                    if *status != SessionStatus::Loading && !already_loaded {
+                        let status = session::load(&login, organization)
+                            .map_or(SessionStatus::Active, |saved| SessionStatus::Offered(Box::new(saved)));
+                        Dispatch::<SessionStatus>::new().set(status);
+                    }
+                }
+                || ()
+            },
+            organizations
+        );
+    }
+
+    // Save whenever something worth saving changes, one session per
+    // organization. We wait until at least one page of an organization has
+    // loaded so that starting over on it doesn't wipe out its saved session
+    // before the user has been asked about it.
+    {
+        let login = user.login.clone();
+        use_effect_with_deps(
+            move |(desired_state_map, page_maps, mode)| {
+                let status = Dispatch::<SessionStatus>::new().get();
+                if let (SessionStatus::Active, Some(login)) = (&*status, login) {
+                    for (organization, page_map) in page_maps.iter() {
+                        if page_map.last_page().is_none() {
+                            continue
+                        }
+                        session::save(&login, &SavedSession {
+                            organization: organization.to_owned(),
+                            backend: page_map.backend(),
+                            mode: *mode,
+                            desired_state_map: desired_state_map.for_organization(organization),
+                            page_repo_map: page_map.clone(),
+                            saved_at: Utc::now()
+                        });
+                    }
+                }
+                || ()
+            },
+            (desired_state_map, page_maps, *mode)
+        );
+    }
+
+    let (SessionStatus::Offered(saved), Some(login)) = (&*status, user.login.clone()) else {
+        return html! {}
+    };
+
+    let on_resume: Callback<MouseEvent> = {
+        let saved = saved.clone();
+        Callback::from(move |_| resume(&saved))
+    };
+    let on_start_fresh: Callback<MouseEvent> = {
+        let saved = saved.clone();
+        Callback::from(move |_| start_fresh(&login, &saved))
+    };
+
+    html! {
+        <div class="alert alert-info shadow-lg m-4">
+            <div>
+                <span>
+                    { format!("You have a saved session for {} from {} ({}), with {} repositories loaded and {} selected.",
+                        saved.organization,
+                        describe_age(saved.saved_at, Utc::now()),
+                        saved.saved_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                        saved.desired_state_map.map.len(),
+                        saved.selected_count()) }
+                </span>
+            </div>
+            <div class="flex-none">
+                <button class="btn btn-sm btn-primary" onclick={ on_resume }>{ "Resume previous session" }</button>
+                <button class="btn btn-sm btn-ghost" onclick={ on_start_fresh }>{ "Start fresh" }</button>
+            </div>
+        </div>
+    }
+}
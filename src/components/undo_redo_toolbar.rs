@@ -0,0 +1,75 @@
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement, KeyboardEvent};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::repository::DesiredStateMap;
+
+fn undo(dispatch: &Dispatch<DesiredStateMap>) {
+    dispatch.reduce_mut(|state_map| { state_map.undo(); });
+}
+
+fn redo(dispatch: &Dispatch<DesiredStateMap>) {
+    dispatch.reduce_mut(|state_map| { state_map.redo(); });
+}
+
+// Text fields have their own undo, which we shouldn't take over.
+fn is_text_entry(event: &KeyboardEvent) -> bool {
+    let Some(target) = event.target() else {
+        return false
+    };
+    target.dyn_ref::<HtmlTextAreaElement>().is_some()
+        || target.dyn_ref::<HtmlInputElement>().is_some_and(|input| input.type_() != "checkbox")
+}
+
+/// Undo and redo buttons for changes to the selection, which also listens
+/// for Ctrl+Z (undo) and Ctrl+Shift+Z or Ctrl+Y (redo), or Cmd on a Mac.
+#[function_component(UndoRedoToolbar)]
+pub fn undo_redo_toolbar() -> Html {
+    let (state_map, dispatch) = use_store::<DesiredStateMap>();
+
+    {
+        let dispatch = dispatch.clone();
+        use_effect_with_deps(
+            move |()| {
+                let listener = EventListener::new(&gloo::utils::window(), "keydown", move |event| {
+                    let Some(event) = event.dyn_ref::<KeyboardEvent>() else {
+                        return
+                    };
+                    if !(event.ctrl_key() || event.meta_key()) || is_text_entry(event) {
+                        return
+                    }
+                    match (event.key().to_lowercase().as_str(), event.shift_key()) {
+                        ("z", false) => undo(&dispatch),
+                        ("z", true) | ("y", false) => redo(&dispatch),
+                        _ => return
+                    }
+                    event.prevent_default();
+                });
+                // Dropping the listener removes it.
+                move || drop(listener)
+            },
+            ()
+        );
+    }
+
+    let on_undo: Callback<MouseEvent> = {
+        let dispatch = dispatch.clone();
+        Callback::from(move |_| undo(&dispatch))
+    };
+    let on_redo: Callback<MouseEvent> = Callback::from(move |_| redo(&dispatch));
+    let undo_disabled = !state_map.can_undo();
+    let redo_disabled = !state_map.can_redo();
+
+    html! {
+        <div class="btn-group">
+            <button class="btn btn-sm" title="Undo (Ctrl+Z)" disabled={ undo_disabled } onclick={ on_undo }>
+                { "Undo" }
+            </button>
+            <button class="btn btn-sm" title="Redo (Ctrl+Shift+Z)" disabled={ redo_disabled } onclick={ on_redo }>
+                { "Redo" }
+            </button>
+        </div>
+    }
+}
@@ -0,0 +1,120 @@
+//! Selecting repositories to archive by how long it's been since anyone
+//! worked on them, e.g., "no push in the last 365 days" or "not updated since
+//! 2023-01-01".
+//!
+//! A rule applies to every repository we've loaded from an organization,
+//! ticking the inactive ones and unticking the rest. It stays in force for
+//! that organization, so pages that arrive later start out the same way.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use yewdux::prelude::*;
+
+use crate::repository::{DesiredState, RepoKey, Repository, SelectionMode};
+
+/// Which of GitHub's timestamps counts as the last activity.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    /// The last push to any branch.
+    #[default]
+    Pushed,
+    /// The last change to the repository itself, which includes things like
+    /// its description and settings as well as pushes.
+    Updated
+}
+
+impl Activity {
+    #[must_use]
+    pub const fn of(self, repo: &Repository) -> DateTime<Local> {
+        match self {
+            Self::Pushed => repo.pushed_at,
+            Self::Updated => repo.updated_at,
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Pushed => "pushed to",
+            Self::Updated => "updated",
+        }
+    }
+}
+
+/// How long ago counts as inactive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    /// Nothing in the last this many days.
+    DaysAgo(u32),
+    /// Nothing since the start of this (local) day.
+    Before(NaiveDate)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InactivityRule {
+    pub activity: Activity,
+    pub threshold: Threshold
+}
+
+impl InactivityRule {
+    /// Repositories whose last activity is earlier than this are inactive.
+    #[must_use]
+    pub fn cutoff(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self.threshold {
+            Threshold::DaysAgo(days) => now - Duration::days(i64::from(days)),
+            // Midnight can be skipped by a daylight saving change, in which
+            // case the day starts at whenever the clocks jump to.
+            Threshold::Before(date) => (0..24)
+                .find_map(|hour| date.and_hms_opt(hour, 0, 0).and_then(|time| Local.from_local_datetime(&time).earliest()))
+                .unwrap_or(now),
+        }
+    }
+
+    #[must_use]
+    pub fn is_inactive(&self, repo: &Repository, now: DateTime<Local>) -> bool {
+        self.activity.of(repo) < self.cutoff(now)
+    }
+
+    /// Tick the inactive repositories in `repos` for archiving and untick the
+    /// others. Repositories that are already archived are left out, since
+    /// there's nothing to decide about them.
+    #[must_use]
+    pub fn changes<'a>(&self, repos: impl IntoIterator<Item = (&'a RepoKey, &'a Repository)>, now: DateTime<Local>) -> Vec<(RepoKey, DesiredState)> {
+        let cutoff = self.cutoff(now);
+        repos.into_iter()
+            .filter(|(_, repo)| SelectionMode::Archive.can_select(repo))
+            .map(|(key, repo)| (key.clone(), DesiredState::from_paginator_state(self.activity.of(repo) < cutoff, SelectionMode::Archive)))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let activity = self.activity.label();
+        match self.threshold {
+            Threshold::DaysAgo(days) => format!("not {activity} in the last {days} day{}", if days == 1 { "" } else { "s" }),
+            Threshold::Before(date) => format!("not {activity} since {}", date.format("%Y-%m-%d")),
+        }
+    }
+}
+
+/// The rule in force for each organization, if any.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+pub struct InactivityRules {
+    rules: BTreeMap<String, InactivityRule>
+}
+
+impl InactivityRules {
+    #[must_use]
+    pub fn get(&self, organization: &str) -> Option<&InactivityRule> {
+        self.rules.get(organization)
+    }
+
+    pub fn set(&mut self, organization: &str, rule: InactivityRule) {
+        self.rules.insert(organization.to_owned(), rule);
+    }
+
+    pub fn remove(&mut self, organization: &str) {
+        self.rules.remove(organization);
+    }
+}
@@ -10,6 +10,13 @@ pub mod services;
 pub mod components;
 pub mod repository;
 pub mod page_repo_map;
+pub mod link_header;
+pub mod plan;
+pub mod name_filter;
+pub mod inactivity;
+pub mod classroom;
+pub mod policy;
+pub mod search;
 
 #[derive(Clone, Routable, PartialEq, Eq)]
 pub enum Route {
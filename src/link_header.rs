@@ -0,0 +1,295 @@
+//! A parser for HTTP `Link` headers ([RFC 8288](https://www.rfc-editor.org/rfc/rfc8288)),
+//! which is how GitHub tells us about the other pages of a paginated list.
+//!
+//! GitHub's headers look like:
+//!
+//! ```text
+//! <https://api.github.com/organizations/18425666/repos?page=1&per_page=5>; rel="prev", <https://api.github.com/organizations/18425666/repos?page=3&per_page=5>; rel="next", <https://api.github.com/organizations/18425666/repos?page=5&per_page=5>; rel="last", <https://api.github.com/organizations/18425666/repos?page=1&per_page=5>; rel="first"
+//! ```
+//!
+//! We don't split on commas, since they can appear inside both URLs and
+//! quoted parameter values; instead we walk through the header a piece at
+//! a time, the way the grammar in the RFC describes it.
+
+use std::fmt;
+
+use url::Url;
+
+use crate::page_repo_map::PageNumber;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkHeaderError {
+    /// We expected a `<` starting a link target at this byte offset.
+    ExpectedTarget(usize),
+    /// The `<` at this byte offset never had a matching `>`.
+    UnterminatedTarget(usize),
+    /// We expected a `;` (starting a parameter) or a `,` (starting the next
+    /// link) at this byte offset.
+    ExpectedSeparator(usize),
+    /// There was a `;` with no parameter name after it at this byte offset.
+    ExpectedParameterName(usize),
+    /// The quoted string starting at this byte offset was never closed.
+    UnterminatedQuotedString(usize),
+    /// A link had a `page` query parameter that wasn't a page number.
+    InvalidPageNumber { target: String, page: String },
+}
+
+impl fmt::Display for LinkHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExpectedTarget(offset) => write!(f, "expected `<` at offset {offset}"),
+            Self::UnterminatedTarget(offset) => write!(f, "the `<` at offset {offset} is never closed"),
+            Self::ExpectedSeparator(offset) => write!(f, "expected `;` or `,` at offset {offset}"),
+            Self::ExpectedParameterName(offset) => write!(f, "expected a parameter name at offset {offset}"),
+            Self::UnterminatedQuotedString(offset) => write!(f, "the quoted string at offset {offset} is never closed"),
+            Self::InvalidPageNumber { target, page } => write!(f, "`{page}` isn't a valid page number in <{target}>"),
+        }
+    }
+}
+
+/// One link from a `Link` header: the target URL and its parameters.
+///
+/// Parameter names are case-insensitive, so we store them in lowercase.
+/// Values are stored as they appear, with any quoting removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub target: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl Link {
+    /// The value of the first parameter called `name`, if there is one.
+    #[must_use]
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param_name, _)| param_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether this link has the given relation type. The `rel` parameter can
+    /// hold several (e.g., `rel="next last"`), and they're case-insensitive.
+    #[must_use]
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.param("rel")
+            .is_some_and(|rels| rels.split_ascii_whitespace().any(|r| r.eq_ignore_ascii_case(rel)))
+    }
+
+    /// The `page` query parameter of the target, if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LinkHeaderError::InvalidPageNumber` if there's a `page`
+    /// parameter that isn't a number.
+    pub fn page(&self) -> Result<Option<PageNumber>, LinkHeaderError> {
+        // The target can be relative, in which case it doesn't matter what we resolve it against.
+        let url = Url::parse(&self.target)
+            .or_else(|_| Url::parse("https://localhost/").and_then(|base| base.join(&self.target)));
+        let Ok(url) = url else {
+            return Ok(None)
+        };
+        let page = url.query_pairs().find(|(name, _)| name == "page").map(|(_, value)| value);
+        page.map(|page| page.parse::<PageNumber>()
+                .map_err(|_| LinkHeaderError::InvalidPageNumber {
+                    target: self.target.clone(),
+                    page: page.into_owned()
+                }))
+            .transpose()
+    }
+}
+
+/// Writes the link back out in header form, quoting every parameter value.
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.target)?;
+        for (name, value) in &self.params {
+            write!(f, "; {name}=\"")?;
+            for c in value.chars() {
+                if matches!(c, '"' | '\\') {
+                    write!(f, "\\")?;
+                }
+                write!(f, "{c}")?;
+            }
+            write!(f, "\"")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a whole `Link` header into its links, in the order they appear.
+///
+/// # Errors
+///
+/// Returns a `LinkHeaderError` saying where things went wrong if the
+/// header isn't well formed.
+pub fn parse(header: &str) -> Result<Vec<Link>, LinkHeaderError> {
+    Parser { input: header, pos: 0 }.links()
+}
+
+/// A link to one of the pages of a paginated list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageLink {
+    pub url: String,
+    pub page: Option<PageNumber>,
+}
+
+/// The `first`, `prev`, `next`, and `last` links from a paginated response.
+///
+/// Any of them can be missing; e.g., GitHub leaves out `prev` and `first`
+/// on the first page, and `next` and `last` on the last page.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PaginationLinks {
+    pub first: Option<PageLink>,
+    pub prev: Option<PageLink>,
+    pub next: Option<PageLink>,
+    pub last: Option<PageLink>,
+}
+
+impl PaginationLinks {
+    /// Pick the pagination links out of a `Link` header. If more than one
+    /// link has the same relation, we use the first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LinkHeaderError` if the header isn't well formed, or if
+    /// one of the pagination links has a `page` that isn't a number.
+    pub fn parse(header: &str) -> Result<Self, LinkHeaderError> {
+        let links = parse(header)?;
+        let find = |rel: &str| {
+            links.iter()
+                .find(|link| link.has_rel(rel))
+                .map(|link| Ok(PageLink { url: link.target.clone(), page: link.page()? }))
+                .transpose()
+        };
+        Ok(Self {
+            first: find("first")?,
+            prev: find("prev")?,
+            next: find("next")?,
+            last: find("last")?,
+        })
+    }
+
+    /// The number of the last page. GitHub leaves out the `last` link when
+    /// we're on the last page, so in that case it's `current_page`.
+    #[must_use]
+    pub fn last_page(&self, current_page: PageNumber) -> PageNumber {
+        self.last
+            .as_ref()
+            .and_then(|last| last.page)
+            .unwrap_or(current_page)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    // A byte offset into `input`. It only ever stops on ASCII characters
+    // (or the end), so it's always safe to slice at.
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn links(mut self) -> Result<Vec<Link>, LinkHeaderError> {
+        let mut links = Vec::new();
+        loop {
+            // The list syntax allows empty elements, so skip any extra commas.
+            while matches!(self.peek(), Some(b' ' | b'\t' | b',')) {
+                self.pos += 1;
+            }
+            if self.peek().is_none() {
+                return Ok(links)
+            }
+            links.push(self.link()?);
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some(b',') => {},
+                Some(_) => return Err(LinkHeaderError::ExpectedSeparator(self.pos)),
+            }
+        }
+    }
+
+    fn link(&mut self) -> Result<Link, LinkHeaderError> {
+        let start = self.pos;
+        if self.peek() != Some(b'<') {
+            return Err(LinkHeaderError::ExpectedTarget(start))
+        }
+        let target_len = self.input[start + 1..]
+            .find('>')
+            .ok_or(LinkHeaderError::UnterminatedTarget(start))?;
+        let target = self.input[start + 1..start + 1 + target_len].to_owned();
+        self.pos = start + target_len + 2;
+
+        let mut params = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b';') {
+                return Ok(Link { target, params })
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let name = self.take_while(is_token_char);
+            if name.is_empty() {
+                return Err(LinkHeaderError::ExpectedParameterName(self.pos))
+            }
+            let name = name.to_ascii_lowercase();
+            self.skip_whitespace();
+            let value = if self.peek() == Some(b'=') {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.peek() == Some(b'"') {
+                    self.quoted_string()?
+                } else {
+                    // Strictly this should be a token, but we're a little more forgiving
+                    // so that, e.g., unquoted URLs in `anchor` parameters still work.
+                    self.take_while(|c| !matches!(c, b';' | b',' | b' ' | b'\t')).to_owned()
+                }
+            } else {
+                String::new()
+            };
+            params.push((name, value));
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(&predicate) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn quoted_string(&mut self) -> Result<String, LinkHeaderError> {
+        let start = self.pos;
+        let mut value = String::new();
+        let mut chars = self.input[start + 1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos = start + 1 + i + 1;
+                    return Ok(value)
+                },
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+        Err(LinkHeaderError::UnterminatedQuotedString(start))
+    }
+}
+
+/// The characters allowed in a token (RFC 9110, section 5.6.2).
+const fn is_token_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*'
+        | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~')
+}
@@ -13,10 +13,15 @@ use yewdux::prelude::*;
 use ice_repos::{components::{
     welcome::Welcome,
     about::About,
+    github_api_provider::GitHubApiProvider,
+    rate_limit_status::RateLimitStatus,
+    etag_cache_status::EtagCacheStatus,
     organization_entry::OrganizationEntry,
+    organization_tabs::OrganizationTabs,
     repository_paginator::RepositoryPaginator,
-    review_and_submit::ReviewAndSubmit
-}, repository::Organization, Route};
+    review_and_submit::ReviewAndSubmit,
+    session_manager::SessionManager
+}, repository::Organizations, Route};
 
 // ===================================================================================
 // for {username}.github.io/{repo_name}
@@ -49,8 +54,8 @@ fn switch(routes: &Route) -> Html {
 
 #[function_component(HomePage)]
 fn home_page() -> Html {
-    let (organization, _) = use_store::<Organization>();
-    let organization = organization.name.as_ref();
+    let (organizations, _) = use_store::<Organizations>();
+    let organization = organizations.current.as_ref();
 
     html! {
         <div class="grid grid-cols-1 divide-y flex flex-col space-y-8 m-16">
@@ -66,8 +71,9 @@ fn home_page() -> Html {
             //   access any part of the global state. 
             if let Some(organization) = organization {
                 <div>
-                    <h2 class="text-2xl">{ format!("The list of repositories for the organization {}", organization) }</h2>
-                    <RepositoryPaginator organization={ organization.clone() } />
+                    <OrganizationTabs />
+                    <h2 class="text-2xl">{ format!("The list of repositories for {}", organizations.source(organization).describe()) }</h2>
+                    <RepositoryPaginator key={ organization.clone() } organization={ organization.clone() } />
                 </div>
             }
 
@@ -102,9 +108,14 @@ fn app() -> Html {
             <Authenticated>
                 <p> <button onclick={logout}>{ "Logout" }</button> </p>
                 <h1>{"Authenticated!"}</h1>
-                <BrowserRouter>
-                    <Switch<RootRoute> render={Switch::render(root_route)}/>
-                </BrowserRouter>
+                <RateLimitStatus/>
+                <EtagCacheStatus/>
+                <GitHubApiProvider>
+                    <SessionManager/>
+                    <BrowserRouter>
+                        <Switch<RootRoute> render={Switch::render(root_route)}/>
+                    </BrowserRouter>
+                </GitHubApiProvider>
             </Authenticated>
             <NotAuthenticated>
                 <p> 
@@ -0,0 +1,167 @@
+//! Filtering repositories by name, with either a glob (`lab-0*`) or a
+//! regular expression (`^lab-0[1-3]-`).
+//!
+//! Globs are matched here, and understand `*` (any run of characters), `?`
+//! (any one character), and `[...]` (any of the characters in the brackets,
+//! which can include ranges like `a-z`, or none of them if it starts with `!`
+//! or `^`). Regular expressions are handed to the browser's `RegExp`, so they
+//! use JavaScript's syntax. Either way the whole name has to match, and case
+//! doesn't matter, since GitHub ignores it in repository names.
+
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Whether the filter is a glob or a regular expression.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    #[default]
+    Glob,
+    Regex
+}
+
+/// What the user has typed into the filter bar.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NameFilter {
+    pub kind: PatternKind,
+    pub pattern: String
+}
+
+impl NameFilter {
+    /// Whether there's anything to filter by. An empty filter matches everything.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.pattern.trim().is_empty()
+    }
+
+    /// Get ready to match names, or say what's wrong with the pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns the browser's description of the problem if the pattern is
+    /// a regular expression it won't accept.
+    pub fn matcher(&self) -> Result<NameMatcher, String> {
+        let pattern = self.pattern.trim();
+        if pattern.is_empty() {
+            return Ok(NameMatcher::Everything)
+        }
+        match self.kind {
+            PatternKind::Glob => Ok(NameMatcher::Glob(pattern.to_lowercase().chars().collect())),
+            PatternKind::Regex => compile_regex(pattern).map(NameMatcher::Regex)
+        }
+    }
+}
+
+/// A compiled `NameFilter`.
+#[derive(Debug, Clone)]
+pub enum NameMatcher {
+    Everything,
+    Glob(Vec<char>),
+    Regex(js_sys::RegExp)
+}
+
+impl NameMatcher {
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Everything => true,
+            Self::Glob(pattern) => glob_matches(pattern, &name.to_lowercase().chars().collect::<Vec<_>>()),
+            Self::Regex(regex) => regex.test(name)
+        }
+    }
+}
+
+// `new RegExp` throws on a bad pattern, which would abort us if we called it
+// directly, so we go through `Reflect::construct` to get the exception back.
+fn compile_regex(pattern: &str) -> Result<js_sys::RegExp, String> {
+    let constructor: js_sys::Function = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("RegExp"))
+        .and_then(JsCast::dyn_into)
+        .map_err(|_| "Regular expressions aren't available".to_owned())?;
+    // Anchor the pattern so it has to match the whole name, like a glob does.
+    let args = js_sys::Array::of2(&JsValue::from_str(&format!("^(?:{pattern})$")), &JsValue::from_str("i"));
+    js_sys::Reflect::construct(&constructor, &args)
+        .map(JsCast::unchecked_into)
+        .map_err(|err| err.dyn_ref::<js_sys::Error>()
+            .map_or_else(|| format!("`{pattern}` isn't a valid regular expression"), |err| String::from(err.message())))
+}
+
+/// Whether `name` matches the glob `pattern`. Both should already be lowercase
+/// if case shouldn't matter.
+#[must_use]
+pub fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    // The usual backtracking matcher: when we hit a `*`, remember where it was
+    // and how much of the name it had eaten, so that if the rest of the pattern
+    // doesn't match we can come back and have it eat one more character.
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+                continue
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+                continue
+            }
+            Some('[') => {
+                if let Some((matched, end)) = match_class(pattern, p, name[n]) {
+                    if matched {
+                        p = end;
+                        n += 1;
+                        continue
+                    }
+                } else if name[n] == '[' {
+                    // An unclosed `[` is just a `[`.
+                    p += 1;
+                    n += 1;
+                    continue
+                }
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+                continue
+            }
+            _ => ()
+        }
+        match star {
+            Some((star_p, star_n)) => {
+                star = Some((star_p, star_n + 1));
+                p = star_p + 1;
+                n = star_n + 1;
+            }
+            None => return false
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Match `c` against the character class starting with the `[` at
+/// `pattern[start]`. Returns whether it matched and the index just past the
+/// closing `]`, or `None` if the class is never closed.
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(pattern.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let current = *pattern.get(i)?;
+        // A `]` right at the start is part of the class rather than the end of it.
+        if current == ']' && !first {
+            return Some((matched != negated, i + 1))
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|end| *end != ']') {
+            let end = pattern[i + 2];
+            matched |= (current..=end).contains(&c);
+            i += 3;
+        } else {
+            matched |= current == c;
+            i += 1;
+        }
+    }
+}
@@ -1,33 +1,97 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use serde::{Deserialize, Serialize};
 use yewdux::store::Store;
 
 use crate::repository::RepoId;
+use crate::services::github_api::{ListingBackend, RepoSource};
 
 pub type PageNumber = usize;
 
-#[derive(Default, Debug, Store, Eq, PartialEq, Clone)]
+/// Which repositories are on each of the pages we've loaded so far, and
+/// where those pages came from.
+#[derive(Default, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct PageRepoMap {
+    // The source and backend the pages were loaded from. If either
+    // changes, the pages are no longer any use and we start over.
+    source: Option<RepoSource>,
+    backend: ListingBackend,
+    // This is `None` until we've loaded the first page and found out
+    // how many pages there are.
+    last_page: Option<PageNumber>,
     map: HashMap<PageNumber, Vec<RepoId>>
 }
 
 impl PageRepoMap {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty map for pages from `source` using `backend`.
+    #[must_use]
+    pub fn for_source(source: RepoSource, backend: ListingBackend) -> Self {
         Self {
-            map: HashMap::new()
+            source: Some(source),
+            backend,
+            ..Self::default()
         }
     }
 
+    /// Whether these pages came from `source` using `backend`.
+    #[must_use]
+    pub fn is_for(&self, source: &RepoSource, backend: ListingBackend) -> bool {
+        self.source.as_ref() == Some(source) && self.backend == backend
+    }
+
+    /// Where these pages came from, if anywhere.
+    #[must_use]
+    pub const fn source(&self) -> Option<&RepoSource> {
+        self.source.as_ref()
+    }
+
+    #[must_use]
+    pub const fn backend(&self) -> ListingBackend {
+        self.backend
+    }
+
+    #[must_use]
+    pub const fn last_page(&self) -> Option<PageNumber> {
+        self.last_page
+    }
+
+    pub const fn set_last_page(&mut self, last_page: PageNumber) {
+        self.last_page = Some(last_page);
+    }
+
     #[must_use]
     pub fn has_loaded_page(&self, page_number: PageNumber) -> bool {
         self.map.contains_key(&page_number)
     }
 
+    /// How many of the pages we've loaded.
+    #[must_use]
+    pub fn loaded_page_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// The first page we haven't loaded yet, or `None` if we've loaded them
+    /// all (or don't know how many there are yet).
+    #[must_use]
+    pub fn first_missing_page(&self) -> Option<PageNumber> {
+        (1..=self.last_page?).find(|page_number| !self.has_loaded_page(*page_number))
+    }
+
     #[must_use]
     pub fn get_repo_ids(&self, page_number: PageNumber) -> Option<Vec<RepoId>> {
         self.map.get(&page_number).cloned()
     }
 
+    /// The page that repository `id` is on, if we've loaded it.
+    #[must_use]
+    pub fn page_of(&self, id: RepoId) -> Option<PageNumber> {
+        self.map.iter().find_map(|(page_number, ids)| ids.contains(&id).then_some(*page_number))
+    }
+
     /// # Panics
     ///
     /// Will panic if `page_number` is already in the `PageRepoMap`. We
@@ -38,3 +102,62 @@ impl PageRepoMap {
         self.map.insert(page_number, repo_ids);
     }
 }
+
+/// The pages we've loaded for each of the organizations we're working on,
+/// so that switching between them doesn't mean loading everything again.
+#[derive(Default, Debug, Store, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PageRepoMaps {
+    maps: BTreeMap<String, PageRepoMap>,
+    // Pages to open the paginator at the next time it shows each organization,
+    // e.g., when a search result in another organization is followed.
+    #[serde(skip)]
+    requested_pages: BTreeMap<String, PageNumber>
+}
+
+impl PageRepoMaps {
+    #[must_use]
+    pub fn get(&self, organization: &str) -> Option<&PageRepoMap> {
+        self.maps.get(organization)
+    }
+
+    pub fn get_mut(&mut self, organization: &str) -> Option<&mut PageRepoMap> {
+        self.maps.get_mut(organization)
+    }
+
+    /// Whether we have pages for `organization` that were loaded from `source`
+    /// using `backend`.
+    #[must_use]
+    pub fn is_for(&self, organization: &str, source: &RepoSource, backend: ListingBackend) -> bool {
+        self.get(organization).is_some_and(|page_map| page_map.is_for(source, backend))
+    }
+
+    /// Replace whatever pages we had for `organization`.
+    pub fn insert(&mut self, organization: &str, page_map: PageRepoMap) {
+        self.maps.insert(organization.to_owned(), page_map);
+    }
+
+    pub fn remove(&mut self, organization: &str) {
+        self.maps.remove(organization);
+        self.requested_pages.remove(organization);
+    }
+
+    /// Have the paginator show `page_number` next time it shows `organization`.
+    pub fn request_page(&mut self, organization: &str, page_number: PageNumber) {
+        self.requested_pages.insert(organization.to_owned(), page_number);
+    }
+
+    #[must_use]
+    pub fn requested_page(&self, organization: &str) -> Option<PageNumber> {
+        self.requested_pages.get(organization).copied()
+    }
+
+    /// Forget the page requested for `organization`, once it's been shown.
+    pub fn clear_requested_page(&mut self, organization: &str) {
+        self.requested_pages.remove(organization);
+    }
+
+    /// Each organization and its pages, in order of organization name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PageRepoMap)> {
+        self.maps.iter().map(|(organization, page_map)| (organization.as_str(), page_map))
+    }
+}
@@ -0,0 +1,371 @@
+//! Archive plans: the selection in a `DesiredStateMap`, written out as JSON or
+//! CSV so that someone else can look it over (or edit it in a spreadsheet)
+//! before anything is archived, and then read back in.
+//!
+//! A plan only records what we intend to do. When one is imported we check
+//! each entry against what GitHub tells us now, and report the ones that
+//! no longer make sense rather than applying them.
+
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::repository::{DesiredState, DesiredStateMap, Phase, RepoId, RepoKey, TransitionError};
+
+const CSV_HEADER: [&str; 6] = ["organization", "id", "name", "desired_state", "archived", "pushed_at"];
+
+/// One repository in a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanEntry {
+    pub organization: String,
+    pub id: RepoId,
+    pub name: String,
+    pub desired_state: DesiredState,
+    /// Whether the repository was archived when the plan was made. If that's
+    /// changed by the time the plan is imported, the plan is out of date.
+    pub archived: bool,
+    pub pushed_at: DateTime<Local>,
+}
+
+impl PlanEntry {
+    #[must_use]
+    pub fn key(&self) -> RepoKey {
+        RepoKey::new(&self.organization, self.id)
+    }
+
+    /// The key for this repository under the name we've loaded its organization
+    /// as, if it's one of `organizations`. GitHub ignores case in organization
+    /// names, so a plan made for `MyOrg` applies to `myorg` too.
+    #[must_use]
+    pub fn loaded_key(&self, organizations: &[String]) -> Option<RepoKey> {
+        organizations.iter()
+            .find(|organization| organization.eq_ignore_ascii_case(&self.organization))
+            .map(|organization| RepoKey::new(organization, self.id))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub entries: Vec<PlanEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    Json,
+    Csv,
+}
+
+impl PlanFormat {
+    /// Guess the format from a file name, e.g., one the user picked to import.
+    #[must_use]
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    #[must_use]
+    pub const fn mime_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    Json(String),
+    /// Something was wrong with the CSV on this (1-based) line.
+    Csv { line: usize, message: String },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(message) => write!(f, "the plan isn't valid JSON: {message}"),
+            Self::Csv { line, message } => write!(f, "line {line} of the plan: {message}"),
+        }
+    }
+}
+
+/// What happened to each entry when a plan was imported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Entries whose desired state we changed to match the plan.
+    pub applied: Vec<PlanEntry>,
+    /// Entries that already matched the plan.
+    pub unchanged: Vec<PlanEntry>,
+    /// Entries for repositories that don't exist anymore (or that we couldn't see).
+    pub missing: Vec<PlanEntry>,
+    /// Entries for repositories that have been archived or unarchived since the
+    /// plan was made.
+    pub archive_status_changed: Vec<PlanEntry>,
+    /// Entries for organizations we aren't working on.
+    pub other_organization: Vec<PlanEntry>,
+    /// Entries the transition table wouldn't let us apply. Every selection the
+    /// paginator can make is reachable from every state, so this should stay
+    /// empty, but if it doesn't we'd rather say so than drop entries silently.
+    pub rejected: Vec<(PlanEntry, TransitionError)>,
+}
+
+impl ImportReport {
+    /// Whether any entries were left out.
+    #[must_use]
+    pub const fn has_problems(&self) -> bool {
+        !(self.missing.is_empty()
+            && self.archive_status_changed.is_empty()
+            && self.other_organization.is_empty()
+            && self.rejected.is_empty())
+    }
+}
+
+impl Plan {
+    /// A plan with every repository we've loaded, from every organization,
+    /// in the order they appear in `state_map`.
+    #[must_use]
+    pub fn from_state_map(state_map: &DesiredStateMap) -> Self {
+        let entries = state_map.map.iter()
+            .map(|(key, (repo, desired_state))| PlanEntry {
+                organization: key.organization.clone(),
+                id: repo.id,
+                name: repo.name.clone(),
+                desired_state: *desired_state,
+                archived: repo.archived,
+                pushed_at: repo.pushed_at,
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// # Errors
+    ///
+    /// Will return an error if `text` isn't a plan in the given format.
+    pub fn parse(format: PlanFormat, text: &str) -> Result<Self, PlanError> {
+        match format {
+            PlanFormat::Json => Self::from_json(text),
+            PlanFormat::Csv => Self::from_csv(text),
+        }
+    }
+
+    #[must_use]
+    pub fn render(&self, format: PlanFormat) -> String {
+        match format {
+            PlanFormat::Json => self.to_json(),
+            PlanFormat::Csv => self.to_csv(),
+        }
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs with string keys can't fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// # Errors
+    ///
+    /// Will return an error if `text` isn't valid JSON, or is missing any fields.
+    pub fn from_json(text: &str) -> Result<Self, PlanError> {
+        serde_json::from_str(text).map_err(|err| PlanError::Json(err.to_string()))
+    }
+
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.join(",");
+        csv.push_str("\r\n");
+        for entry in &self.entries {
+            let fields = [
+                csv_field(&entry.organization),
+                entry.id.to_string(),
+                csv_field(&entry.name),
+                state_name(entry.desired_state),
+                entry.archived.to_string(),
+                entry.pushed_at.to_rfc3339(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// Read a plan from CSV. The first row must be the header that `to_csv`
+    /// writes, although the columns can be in any order; spreadsheets often
+    /// rearrange them.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the CSV is malformed, a column is missing,
+    /// or a field can't be parsed.
+    pub fn from_csv(text: &str) -> Result<Self, PlanError> {
+        let mut records = csv_records(text)?.into_iter();
+        let Some((header_line, header)) = records.next() else {
+            return Err(PlanError::Csv { line: 1, message: "the plan is empty".to_owned() })
+        };
+        let mut columns = [0; CSV_HEADER.len()];
+        for (column, name) in columns.iter_mut().zip(CSV_HEADER) {
+            *column = header.iter().position(|field| field.trim() == name)
+                .ok_or_else(|| PlanError::Csv { line: header_line, message: format!("there's no `{name}` column") })?;
+        }
+
+        let mut entries = Vec::new();
+        for (line, record) in records {
+            // Spreadsheets like to leave blank rows at the end.
+            if record.iter().all(|field| field.trim().is_empty()) {
+                continue
+            }
+            let error = |message: String| PlanError::Csv { line, message };
+            let mut fields = [""; CSV_HEADER.len()];
+            for ((field, column), name) in fields.iter_mut().zip(columns).zip(CSV_HEADER) {
+                *field = record.get(column).map(|field| field.trim())
+                    .ok_or_else(|| error(format!("there's no value for `{name}`")))?;
+            }
+            let [organization, id, name, desired_state, archived, pushed_at] = fields;
+            entries.push(PlanEntry {
+                organization: organization.to_owned(),
+                id: id.parse().map_err(|_| error(format!("`{id}` isn't a repository id")))?,
+                name: name.to_owned(),
+                desired_state: parse_state(desired_state)
+                    .ok_or_else(|| error(format!("`{desired_state}` isn't a desired state")))?,
+                archived: archived.to_ascii_lowercase().parse()
+                    .map_err(|_| error(format!("`{archived}` isn't true or false")))?,
+                pushed_at: DateTime::parse_from_rfc3339(pushed_at)
+                    .map_err(|err| error(format!("`{pushed_at}` isn't a date and time: {err}")))?
+                    .with_timezone(&Local),
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// The entries for `organizations` whose repositories aren't in `state_map`,
+    /// e.g., because they're on pages we haven't loaded yet. These need looking
+    /// up before `apply` can tell whether they still exist.
+    #[must_use]
+    pub fn entries_not_in<'a>(&'a self, organizations: &[String], state_map: &DesiredStateMap) -> Vec<&'a PlanEntry> {
+        self.entries.iter()
+            .filter(|entry| entry.loaded_key(organizations).is_some_and(|key| !state_map.map.contains_key(&key)))
+            .collect()
+    }
+
+    /// Apply the entries for `organizations` to `state_map`, as a single change
+    /// that can be undone, and report on what happened to each of them.
+    ///
+    /// Only the selection is taken from the plan: a repository is selected in
+    /// `state_map` exactly when it's selected in the plan.
+    pub fn apply(&self, organizations: &[String], state_map: &mut DesiredStateMap) -> ImportReport {
+        let mut report = ImportReport::default();
+        let mut to_apply = Vec::new();
+        for entry in &self.entries {
+            let Some(key) = entry.loaded_key(organizations) else {
+                report.other_organization.push(entry.clone());
+                continue
+            };
+            let Some((repo, current_state)) = state_map.map.get(&key) else {
+                report.missing.push(entry.clone());
+                continue
+            };
+            if repo.archived != entry.archived {
+                report.archive_status_changed.push(entry.clone());
+            } else if current_state.is_selected() == entry.desired_state.is_selected() {
+                report.unchanged.push(entry.clone());
+            } else {
+                let target = match (entry.desired_state.is_selected(), repo.archived) {
+                    (true, false) => DesiredState::Archive,
+                    (true, true) => DesiredState::Unarchive,
+                    (false, false) => DesiredState::Keep,
+                    (false, true) => DesiredState::AlreadyArchived,
+                };
+                to_apply.push((entry, key, target));
+            }
+        }
+
+        let errors = state_map.transition_all(to_apply.iter().map(|(_, key, target)| (key.clone(), *target)), Phase::Paginator);
+        for (entry, key, _) in to_apply {
+            match errors.iter().find(|err| *err.key() == key) {
+                Some(err) => report.rejected.push((entry.clone(), err.clone())),
+                None => report.applied.push(entry.clone()),
+            }
+        }
+        report
+    }
+}
+
+// We name states the same way in CSV as serde does in JSON.
+fn state_name(state: DesiredState) -> String {
+    match serde_json::to_value(state) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{state:?}"),
+    }
+}
+
+fn parse_state(name: &str) -> Option<DesiredState> {
+    serde_json::from_value(serde_json::Value::String(name.to_owned())).ok()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+// Split `text` into records of fields, as described in RFC 4180, pairing each
+// record with the line it starts on. Quoted fields can contain commas, quotes
+// (doubled), and line breaks.
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, PlanError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut in_quotes = false;
+    let mut quote_line = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => (),
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if in_quotes {
+        return Err(PlanError::Csv { line: quote_line, message: "a quoted field is never closed".to_owned() })
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
@@ -0,0 +1,283 @@
+//! Archive policies: an ordered list of rules that decide which repositories
+//! to archive and which to keep.
+//!
+//! A policy is written once, in JSON or TOML, so the same choices can be made
+//! every semester without ticking boxes by hand.
+//!
+//! ```toml
+//! [[rules]]
+//! description = "Never touch templates"
+//! names = ["*-template", "starter-*"]
+//! action = "keep"
+//!
+//! [[rules]]
+//! pushed_within_days = 30
+//! action = "keep"
+//!
+//! [[rules]]
+//! action = "archive"
+//! ```
+//!
+//! The first rule whose conditions all hold decides; a rule with no
+//! conditions matches everything. Repositories that no rule matches, and
+//! those that are already archived, are left alone.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use yewdux::prelude::*;
+
+use crate::inactivity::{Activity, InactivityRule, Threshold};
+use crate::name_filter::glob_matches;
+use crate::repository::{DesiredState, DesiredStateMap, Phase, RepoKey, Repository, SelectionMode, TransitionError, Visibility};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Archive,
+    Keep
+}
+
+impl PolicyAction {
+    #[must_use]
+    pub const fn desired_state(self) -> DesiredState {
+        DesiredState::from_paginator_state(matches!(self, Self::Archive), SelectionMode::Archive)
+    }
+}
+
+/// One rule in a policy. Every condition that's there has to hold for the
+/// rule to match.
+#[allow(clippy::struct_field_names)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Globs, as in the filter bar; the name has to match at least one.
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub pushed_within_days: Option<u32>,
+    #[serde(default, deserialize_with = "date")]
+    pub pushed_before: Option<NaiveDate>,
+    #[serde(default)]
+    pub updated_within_days: Option<u32>,
+    #[serde(default, deserialize_with = "date")]
+    pub updated_before: Option<NaiveDate>,
+    #[serde(default)]
+    pub fork: Option<bool>,
+    #[serde(default)]
+    pub template: Option<bool>,
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    pub action: PolicyAction
+}
+
+// JSON has dates as `YYYY-MM-DD` strings, but TOML has a type of its own
+// for them, which serde sees as a map holding the `toml` crate's `Datetime`.
+fn date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+    struct DateVisitor;
+
+    impl<'de> Visitor<'de> for DateVisitor {
+        type Value = NaiveDate;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a date like 2023-06-01")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<NaiveDate, E> {
+            value.parse().map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<NaiveDate, A::Error> {
+            let datetime = toml::value::Datetime::deserialize(MapAccessDeserializer::new(map))?;
+            match (datetime.date, datetime.time) {
+                (Some(date), None) => NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+                    .ok_or_else(|| de::Error::custom(format!("{datetime} isn't a date"))),
+                _ => Err(de::Error::invalid_value(de::Unexpected::Str(&datetime.to_string()), &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(DateVisitor).map(Some)
+}
+
+impl PolicyRule {
+    #[must_use]
+    pub fn matches(&self, repo: &Repository, now: DateTime<Local>) -> bool {
+        let name: Vec<char> = repo.name.to_lowercase().chars().collect();
+        let inactive = |activity, threshold| InactivityRule { activity, threshold }.is_inactive(repo, now);
+        (self.names.is_empty() || self.names.iter().any(|glob| glob_matches(&glob.to_lowercase().chars().collect::<Vec<_>>(), &name)))
+            && self.pushed_within_days.is_none_or(|days| !inactive(Activity::Pushed, Threshold::DaysAgo(days)))
+            && self.pushed_before.is_none_or(|date| inactive(Activity::Pushed, Threshold::Before(date)))
+            && self.updated_within_days.is_none_or(|days| !inactive(Activity::Updated, Threshold::DaysAgo(days)))
+            && self.updated_before.is_none_or(|date| inactive(Activity::Updated, Threshold::Before(date)))
+            && self.fork.is_none_or(|fork| repo.fork == fork)
+            && self.template.is_none_or(|template| repo.is_template == template)
+            && self.visibility.is_none_or(|visibility| repo.visibility() == visibility)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyFormat {
+    Json,
+    Toml,
+}
+
+impl PolicyFormat {
+    /// Guess the format from a file name, e.g., one the user picked to load.
+    #[must_use]
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    Json(String),
+    /// Something was wrong with the TOML, or the policy in it, on this (1-based) line.
+    Toml { line: usize, message: String },
+    /// The file was valid, but isn't a policy, e.g., a rule has no `action`.
+    Invalid(String),
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(message) => write!(f, "the policy isn't valid JSON: {message}"),
+            Self::Toml { line, message } => write!(f, "line {line} of the policy: {message}"),
+            Self::Invalid(message) => write!(f, "the policy doesn't make sense: {message}"),
+        }
+    }
+}
+
+impl Policy {
+    /// # Errors
+    ///
+    /// Will return an error if `text` isn't a policy in the given format.
+    pub fn parse(format: PolicyFormat, text: &str) -> Result<Self, PolicyError> {
+        match format {
+            PolicyFormat::Json => {
+                let value: serde_json::Value = serde_json::from_str(text).map_err(|err| PolicyError::Json(err.to_string()))?;
+                serde_json::from_value(value).map_err(|err| PolicyError::Invalid(err.to_string()))
+            }
+            PolicyFormat::Toml => toml::from_str(text).map_err(|err| err.span().map_or_else(
+                || PolicyError::Invalid(err.message().to_owned()),
+                |span| PolicyError::Toml { line: text[..span.start].matches('\n').count() + 1, message: err.message().to_owned() })),
+        }
+    }
+
+    /// The index of the first rule that matches `repo`, if any.
+    #[must_use]
+    pub fn decide(&self, repo: &Repository, now: DateTime<Local>) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.matches(repo, now))
+    }
+
+    /// How to refer to the rule at `index` on a card.
+    #[must_use]
+    pub fn label(&self, index: usize) -> String {
+        self.rules.get(index).and_then(|rule| rule.description.as_ref()).map_or_else(
+            || format!("rule {}", index + 1),
+            |description| format!("rule {} ({description})", index + 1))
+    }
+}
+
+/// The policy we've loaded, if any, and which rule decided each repository
+/// it's been applied to. It stays in force, so pages that arrive later are
+/// decided by it too.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+pub struct ArchivePolicy {
+    pub file_name: Option<String>,
+    pub policy: Policy,
+    decisions: BTreeMap<RepoKey, usize>
+}
+
+/// How many repositories applying a policy archived, kept, or left alone.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PolicyReport {
+    pub archive: usize,
+    pub keep: usize,
+    pub unmatched: usize,
+    pub rejected: Vec<TransitionError>
+}
+
+impl ArchivePolicy {
+    #[must_use]
+    pub const fn is_loaded(&self) -> bool {
+        self.file_name.is_some()
+    }
+
+    /// Forget any previous policy (and its decisions) and use `policy` from now on.
+    pub fn load(&mut self, file_name: &str, policy: Policy) {
+        *self = Self { file_name: Some(file_name.to_owned()), policy, decisions: BTreeMap::new() };
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Decide every unarchived repository in `state_map`, from every
+    /// organization, as a single change that can be undone.
+    pub fn apply(&mut self, state_map: &mut DesiredStateMap, now: DateTime<Local>) -> PolicyReport {
+        let mut report = PolicyReport::default();
+        let mut changes = Vec::new();
+        self.decisions.clear();
+        for (key, (repo, _)) in &state_map.map {
+            if !SelectionMode::Archive.can_select(repo) {
+                continue
+            }
+            let Some(index) = self.policy.decide(repo, now) else {
+                report.unmatched += 1;
+                continue
+            };
+            let action = self.policy.rules[index].action;
+            match action {
+                PolicyAction::Archive => report.archive += 1,
+                PolicyAction::Keep => report.keep += 1,
+            }
+            self.decisions.insert(key.clone(), index);
+            changes.push((key.clone(), action.desired_state()));
+        }
+        report.rejected = state_map.transition_all(changes, Phase::Paginator);
+        for err in &report.rejected {
+            self.decisions.remove(err.key());
+        }
+        report
+    }
+
+    /// Decide a repository we've just loaded, returning whether it should
+    /// start out selected for archiving, or `None` if no rule matches.
+    pub fn decide_new(&mut self, key: &RepoKey, repo: &Repository, now: DateTime<Local>) -> Option<bool> {
+        if !self.is_loaded() || !SelectionMode::Archive.can_select(repo) {
+            return None
+        }
+        let index = self.policy.decide(repo, now)?;
+        self.decisions.insert(key.clone(), index);
+        Some(self.policy.rules[index].action == PolicyAction::Archive)
+    }
+
+    /// The rule that decided `key`, as long as the repository is still in the
+    /// state the rule put it in; once the user changes it, the rule didn't
+    /// decide it any more.
+    #[must_use]
+    pub fn decided_by(&self, key: &RepoKey, state: DesiredState) -> Option<String> {
+        let index = *self.decisions.get(key)?;
+        (self.policy.rules.get(index)?.action.desired_state() == state).then(|| self.policy.label(index))
+    }
+}
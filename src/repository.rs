@@ -1,13 +1,75 @@
 use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
 use chrono::{DateTime, Local};
 
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use yewdux::prelude::*;
 
+use crate::services::github_api::RepoSource;
+
 pub type RepoId = usize;
 
+/// Identifies a repository across all the organizations we're working on.
+///
+/// `organization` is the name of the `RepoSource` the repository was listed
+/// from, which for a user's repositories isn't necessarily its owner.
+///
+/// This is written as `organization/id` when serialized, so that it can be
+/// used as a key in JSON objects.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RepoKey {
+    pub organization: String,
+    pub id: RepoId
+}
+
+impl RepoKey {
+    #[must_use]
+    pub fn new(organization: &str, id: RepoId) -> Self {
+        Self { organization: organization.to_owned(), id }
+    }
+
+    /// Who most likely owns the repository, for when the repository itself
+    /// doesn't say: the organization, or for a team (`organization/team`),
+    /// the team's organization.
+    #[must_use]
+    pub fn likely_owner(&self) -> &str {
+        self.organization.split_once('/').map_or(&self.organization, |(organization, _)| organization)
+    }
+}
+
+impl fmt::Display for RepoKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.organization, self.id)
+    }
+}
+
+impl FromStr for RepoKey {
+    type Err = String;
+
+    // Team sources are filed under `organization/team`, so the organization
+    // part can contain a `/`, but the id never does, so the last one separates them.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (organization, id) = s.rsplit_once('/').ok_or_else(|| format!("`{s}` isn't of the form organization/id"))?;
+        let id = id.parse().map_err(|_| format!("`{id}` isn't a repository id"))?;
+        Ok(Self { organization: organization.to_owned(), id })
+    }
+}
+
+impl Serialize for RepoKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RepoKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
 // TODO: Can we use `AttrValue` instead of `String` here
 // and in other places where there are properties?
 // I'm not sure what would be necessary here since
@@ -17,64 +79,327 @@ pub type RepoId = usize;
 // `AttrValue` is supposed to be more efficient
 // because cloning `String`s can be expensive.
 // https://yew.rs/docs/concepts/components/properties#memoryspeed-overhead-of-using-properties
-#[derive(Clone, Eq, PartialEq, Deserialize, Debug)]
+// The bools mirror GitHub's fields one for one.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Repository {
     pub id: RepoId,
     pub name: String,
+    /// `owner/name`. Plans and sessions saved before we listed anything other
+    /// than organizations don't have this.
+    #[serde(default)]
+    pub full_name: Option<String>,
     pub description: Option<String>,
     pub archived: bool,
     pub updated_at: DateTime<Local>,
     pub pushed_at: DateTime<Local>,
+    pub fork: bool,
+    pub private: bool,
+    // Older GitHub Enterprise servers don't send this, in which case
+    // `private` is all we have to go on.
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    #[serde(default)]
+    pub is_template: bool,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub language: Option<String>,
+    /// The size of the repository in kilobytes.
+    pub size: u64,
+    pub default_branch: String,
+    /// On GitHub this includes open pull requests as well as open issues.
+    pub open_issues_count: u32,
+    pub html_url: String,
+    /// What the logged in user is allowed to do with this repository. GitHub
+    /// only includes this when the request is authenticated.
+    #[serde(default)]
+    pub permissions: Option<RepoPermissions>,
+}
+
+impl Repository {
+    #[must_use]
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.unwrap_or(if self.private { Visibility::Private } else { Visibility::Public })
+    }
 
-    // #[serde(flatten)]
-    // extras: HashMap<String, Value>,
+    /// The user or organization that owns this repository, if we know.
+    #[must_use]
+    pub fn owner(&self) -> Option<&str> {
+        self.full_name.as_deref()
+            .and_then(|full_name| full_name.split_once('/'))
+            .map(|(owner, _)| owner)
+    }
+
+    /// Whether the logged in user can archive (or unarchive) this repository,
+    /// which takes admin access. If we don't know, we assume they can and
+    /// let GitHub tell us otherwise.
+    #[must_use]
+    pub fn can_archive(&self) -> bool {
+        self.permissions.as_ref().is_none_or(|permissions| permissions.admin)
+    }
+}
+
+// The REST API uses lowercase names and the GraphQL API uses uppercase ones.
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Visibility {
+    #[serde(rename = "public", alias = "PUBLIC")]
+    Public,
+    #[serde(rename = "private", alias = "PRIVATE")]
+    Private,
+    /// Only visible to members of the enterprise that owns the organization.
+    #[serde(rename = "internal", alias = "INTERNAL")]
+    Internal
+}
+
+impl Visibility {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Private => "private",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct RepoPermissions {
+    pub admin: bool,
+    #[serde(default)]
+    pub maintain: bool,
+    pub push: bool,
+    #[serde(default)]
+    pub triage: bool,
+    pub pull: bool
 }
 
 pub struct DesiredArchiveState {
-    pub id: RepoId,
+    pub key: RepoKey,
     pub desired_archive_state: bool
 }
 
-// TODO: I think we may want to ultimately get rid of
-//   this struct. It's not needed in the Paginator anymore,
-//   and we may not need it in the `OrganizationEntry` component,
-//   but I'm not 100% about that. 
 // TODO: Can we use `AttrValue` instead of `String` here?
 // `AttrValue` is supposed to be more efficient
 // because cloning `String`s can be expensive.
 // https://yew.rs/docs/concepts/components/properties#memoryspeed-overhead-of-using-properties
+/// The organizations we're working on, in the order they were added, and
+/// the one the paginator is showing.
+///
+/// Despite the name, these can also be
+/// users' repositories; `sources` says where each one's repositories come
+/// from, and anything not in there is an organization.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
-pub struct Organization {
-    pub name: Option<String>
+pub struct Organizations {
+    pub names: Vec<String>,
+    pub current: Option<String>,
+    pub sources: BTreeMap<String, RepoSource>
+}
+
+impl Organizations {
+    /// The name we have `name` filed under. GitHub ignores case in
+    /// organization names, so we do too.
+    fn find(&self, name: &str) -> Option<&String> {
+        self.names.iter().find(|existing| existing.eq_ignore_ascii_case(name))
+    }
+
+    /// Add `name` (unless we already have it) and make it the current organization.
+    pub fn add(&mut self, name: &str) -> &mut Self {
+        if let Some(existing) = self.find(name) {
+            self.current = Some(existing.clone());
+        } else {
+            self.names.push(name.to_owned());
+            self.current = Some(name.to_owned());
+        }
+        self
+    }
+
+    /// Add `source` (replacing the source of the same name, if we have one),
+    /// and make it current.
+    pub fn add_source(&mut self, source: RepoSource) -> &mut Self {
+        self.add(&source.name());
+        if let Some(name) = self.current.clone() {
+            self.sources.insert(name, source);
+        }
+        self
+    }
+
+    /// Where the repositories filed under `name` come from.
+    #[must_use]
+    pub fn source(&self, name: &str) -> RepoSource {
+        self.find(name)
+            .and_then(|existing| self.sources.get(existing))
+            .cloned()
+            .unwrap_or_else(|| RepoSource::Organization(name.to_owned()))
+    }
+
+    /// Stop working on `name`. If it was the current organization, the first
+    /// of the others (if any) becomes current.
+    pub fn remove(&mut self, name: &str) -> &mut Self {
+        let Some(existing) = self.find(name).cloned() else {
+            return self
+        };
+        self.names.retain(|other| *other != existing);
+        self.sources.remove(&existing);
+        if self.current.as_ref() == Some(&existing) {
+            self.current = self.names.first().cloned();
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+}
+
+/// Whether we're choosing repositories to archive (the usual case) or
+/// choosing already archived repositories to bring back.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Store, Serialize, Deserialize)]
+pub enum SelectionMode {
+    #[default]
+    Archive,
+    Unarchive
+}
+
+impl SelectionMode {
+    /// The value of GitHub's `archived` flag we want selected repositories to end up with.
+    #[must_use]
+    pub const fn target_archived(self) -> bool {
+        matches!(self, Self::Archive)
+    }
+
+    /// Whether a repository can be selected in this mode: we can only
+    /// archive unarchived repositories, and vice versa.
+    #[must_use]
+    pub const fn can_select(self, repo: &Repository) -> bool {
+        repo.archived != self.target_archived()
+    }
 }
 
-// TODO: Add an `AlreadyArchived` option here and keep all the
-//   the repositories in all the maps regardless of whether they
-//   were archived in advance.
 /// The desired state for a given repository.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DesiredState {
-    /// This repository was already archived and its state can't be change.
+    /// This repository was already archived, and we're leaving it that way.
     AlreadyArchived,
     /// We have chosen in the pagination view to _not_ archive this repository.
     Keep,
     /// We have chosen in the pagination view to archive this repository.
     Archive,
-    /// We have changed from "to archive" to "don't archive" in the review view.
-    KeptInReview
+    /// We have changed from "to archive" (or "to unarchive") to "leave it alone"
+    /// in the review view.
+    KeptInReview,
+    /// This repository was already archived, and we have chosen in unarchive
+    /// mode to unarchive it.
+    Unarchive,
+    /// We asked GitHub to archive this repository, and it did.
+    Archived,
+    /// We asked GitHub to unarchive this repository, and it did.
+    Unarchived,
+    /// We asked GitHub to archive or unarchive this repository, and it
+    /// didn't work. It goes back into the review so it can be tried again.
+    Failed
 }
 
+/// Where a change to a repository's `DesiredState` comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Ticking or unticking a checkbox in the paginator.
+    Paginator,
+    /// Ticking or unticking a checkbox in the review.
+    Review,
+    /// Hearing back from GitHub about a request we sent.
+    Submission
+}
+
+/// Every legal change of `DesiredState`, and where it can happen. Anything
+/// that isn't in here (other than "changing" to the state you're already in)
+/// is rejected by `DesiredStateMap::transition`.
+pub const TRANSITIONS: &[(Phase, DesiredState, DesiredState)] = {
+    use DesiredState::{AlreadyArchived, Archive, Archived, Failed, Keep, KeptInReview, Unarchive, Unarchived};
+    use Phase::{Paginator, Review, Submission};
+    &[
+        // Archive mode in the paginator
+        (Paginator, Archive, Keep),
+        (Paginator, Keep, Archive),
+        (Paginator, KeptInReview, Archive),
+        (Paginator, KeptInReview, Keep),
+        (Paginator, Unarchived, Archive),
+        (Paginator, Unarchived, Keep),
+        // Unarchive mode in the paginator
+        (Paginator, AlreadyArchived, Unarchive),
+        (Paginator, Unarchive, AlreadyArchived),
+        (Paginator, KeptInReview, Unarchive),
+        (Paginator, KeptInReview, AlreadyArchived),
+        (Paginator, Archived, Unarchive),
+        (Paginator, Archived, AlreadyArchived),
+        // Either mode, after a failed request
+        (Paginator, Failed, Archive),
+        (Paginator, Failed, Keep),
+        (Paginator, Failed, Unarchive),
+        (Paginator, Failed, AlreadyArchived),
+
+        (Review, Archive, KeptInReview),
+        (Review, Unarchive, KeptInReview),
+        (Review, KeptInReview, Archive),
+        (Review, KeptInReview, Unarchive),
+        (Review, Failed, Archive),
+        (Review, Failed, Unarchive),
+        (Review, Failed, KeptInReview),
+
+        (Submission, Archive, Archived),
+        // Someone else archived it before we got to it.
+        (Submission, Archive, AlreadyArchived),
+        (Submission, Archive, Failed),
+        (Submission, Unarchive, Unarchived),
+        // Someone else unarchived it before we got to it.
+        (Submission, Unarchive, Keep),
+        (Submission, Unarchive, Failed),
+    ]
+};
+
 impl DesiredState {
+    /// Whether the table allows changing from this state to `to` in `phase`.
+    #[must_use]
+    pub fn can_transition_to(self, to: Self, phase: Phase) -> bool {
+        self == to || TRANSITIONS.contains(&(phase, self, to))
+    }
+
+    /// Whether there's anything `phase` can change this state to.
+    #[must_use]
+    pub fn has_transitions_in(self, phase: Phase) -> bool {
+        TRANSITIONS.iter().any(|&(p, from, _)| p == phase && from == self)
+    }
+
+    /// What GitHub's `archived` flag has to be for a repository to be in
+    /// this state, if it matters. E.g., we can only `Archive` a repository
+    /// that isn't archived yet.
+    #[must_use]
+    pub const fn required_archived_flag(self) -> Option<bool> {
+        match self {
+            Self::Keep | Self::Archive | Self::Unarchived => Some(false),
+            Self::AlreadyArchived | Self::Unarchive | Self::Archived => Some(true),
+            Self::KeptInReview | Self::Failed => None,
+        }
+    }
+
+    /// Whether this state's checkbox is ticked, i.e., we intend to send
+    /// GitHub a request for this repository.
+    #[must_use]
+    pub const fn is_selected(self) -> bool {
+        matches!(self, Self::Archive | Self::Unarchive)
+    }
     /// Convert a boolean, essentially the toggle state of a checkbox in the
     /// Paginator component and convert it into an `ArchiveState`. In the
     /// paginator, we want to use the `Skip` state to indicate that we do not
     /// want to see this archive in the review phase.
     #[must_use]
-    pub const fn from_paginator_state(b: bool) -> Self {
-        if b {
-            Self::Archive
-        } else {
-            Self::Keep
+    pub const fn from_paginator_state(b: bool, mode: SelectionMode) -> Self {
+        match (b, mode) {
+            (true, SelectionMode::Archive) => Self::Archive,
+            (false, SelectionMode::Archive) => Self::Keep,
+            (true, SelectionMode::Unarchive) => Self::Unarchive,
+            (false, SelectionMode::Unarchive) => Self::AlreadyArchived,
         }
     }
 
@@ -83,85 +408,311 @@ impl DesiredState {
     /// the review, we want to use the `SkippedInReview` to indicate that we
     /// do want to continue to see this archive in the review phase.
     #[must_use]
-    pub const fn from_review_state(b: bool) -> Self {
-        if b {
-            Self::Archive
-        } else {
-            Self::KeptInReview
+    pub const fn from_review_state(b: bool, mode: SelectionMode) -> Self {
+        match (b, mode) {
+            (true, SelectionMode::Archive) => Self::Archive,
+            (true, SelectionMode::Unarchive) => Self::Unarchive,
+            (false, _) => Self::KeptInReview,
         }
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+/// Why `DesiredStateMap::transition` refused to make a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    UnknownRepo(RepoKey),
+    /// The transition table doesn't allow this change in this phase.
+    Illegal { key: RepoKey, from: DesiredState, to: DesiredState, phase: Phase },
+    /// The change is in the table, but doesn't fit the repository, e.g.,
+    /// trying to `Archive` a repository that's already archived.
+    WrongArchivedFlag { key: RepoKey, to: DesiredState, archived: bool }
+}
+
+impl TransitionError {
+    /// The repository we couldn't change.
+    #[must_use]
+    pub const fn key(&self) -> &RepoKey {
+        match self {
+            Self::UnknownRepo(key) | Self::Illegal { key, .. } | Self::WrongArchivedFlag { key, .. } => key
+        }
+    }
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownRepo(key) => write!(f, "repository {key} isn't in the map"),
+            Self::Illegal { key, from, to, phase } =>
+                write!(f, "can't change repository {key} from {from:?} to {to:?} in {phase:?}"),
+            Self::WrongArchivedFlag { key, to, archived } =>
+                write!(f, "can't change repository {key} to {to:?} when its archived flag is {archived}"),
+        }
+    }
+}
+
+/// A single change to one repository's `DesiredState`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub key: RepoKey,
+    pub from: DesiredState,
+    pub to: DesiredState
+}
+
+/// The user's changes, so they can be undone and redone. Each entry is
+/// everything that one action changed, so undoing a bulk operation undoes
+/// all of it at once.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct History {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store, Serialize, Deserialize)]
 pub struct DesiredStateMap {
-    // Map from the repository ID as a key, to a pair
-    // containing the Repository struct and a boolean
-    // indicating whether we want to archive that repository
-    // or not.
-    pub map: BTreeMap<RepoId, (Repository, DesiredState)>
+    // Map from the organization and repository ID, to
+    // a pair containing the Repository struct and the
+    // state we want it to end up in.
+    pub map: BTreeMap<RepoKey, (Repository, DesiredState)>,
+    // We don't save the undo history along with the selection.
+    #[serde(skip)]
+    pub history: History
 }
 
 impl DesiredStateMap {
-    pub fn with_repos(&mut self, repositories: &[Repository]) -> &mut Self {
+    /// Add `repositories` from `organization`, leaving the state of any we
+    /// already know about alone.
+    pub fn with_repos(&mut self, organization: &str, repositories: &[Repository]) -> &mut Self {
+        self.with_repos_selected_by(organization, repositories, |_| true)
+    }
+
+    /// Like `with_repos`, but unarchived repositories start out ticked for
+    /// archiving only if `selected` says so. This isn't a change the user can
+    /// undo, any more than loading the page is.
+    pub fn with_repos_selected_by(&mut self, organization: &str, repositories: &[Repository], selected: impl Fn(&Repository) -> bool) -> &mut Self {
         for repo in repositories {
             let initial_state = if repo.archived {
                 DesiredState::AlreadyArchived
             } else {
-                DesiredState::Archive
+                DesiredState::from_paginator_state(selected(repo), SelectionMode::Archive)
             };
-            self.map.entry(repo.id).or_insert((repo.clone(), initial_state));
+            self.map.entry(RepoKey::new(organization, repo.id)).or_insert_with(|| (repo.clone(), initial_state));
         }
         self
     }
 
+    /// Forget everything about `organization`, including any undo history
+    /// for it.
+    pub fn remove_organization(&mut self, organization: &str) -> &mut Self {
+        self.map.retain(|key, _| key.organization != organization);
+        for changes in self.history.undo.iter_mut().chain(self.history.redo.iter_mut()) {
+            changes.retain(|change| change.key.organization != organization);
+        }
+        self.history.undo.retain(|changes| !changes.is_empty());
+        self.history.redo.retain(|changes| !changes.is_empty());
+        self
+    }
+
+    /// Just the repositories from `organization`, without any undo history.
     #[must_use]
-    pub fn get_desired_state(&self, id: RepoId) -> Option<bool> {
-        self.map
-            .get(&id)
-            .map(|(_, desired_state)| matches!(desired_state, DesiredState::Archive))
+    pub fn for_organization(&self, organization: &str) -> Self {
+        Self {
+            map: self.map.iter()
+                .filter(|(key, _)| key.organization == organization)
+                .map(|(key, entry)| (key.clone(), entry.clone()))
+                .collect(),
+            history: History::default()
+        }
     }
 
-    pub fn update_desired_state(&mut self, id: RepoId, desired_state: DesiredState) -> &mut Self {
-        web_sys::console::log_1(&format!("Updating {id} to {desired_state:?}").into());
-        self.map.entry(id).and_modify(|p| { p.1 = desired_state });
-        web_sys::console::log_1(&format!("The resulting map was {self:?}").into());
+    /// Add everything in `other`, replacing whatever we had for the same
+    /// repositories. This isn't a change the user can undo.
+    pub fn merge(&mut self, other: Self) -> &mut Self {
+        self.map.extend(other.map);
         self
     }
 
+    /// The organizations we have repositories for, in order.
+    #[must_use]
+    pub fn organizations(&self) -> Vec<&str> {
+        let mut organizations: Vec<&str> = self.map.keys().map(|key| key.organization.as_str()).collect();
+        organizations.dedup();
+        organizations
+    }
+
+    #[must_use]
+    pub fn get_desired_state(&self, key: &RepoKey) -> Option<bool> {
+        self.map
+            .get(key)
+            .map(|(_, desired_state)| desired_state.is_selected())
+    }
+
+    /// Change the state of repository `key` to `to`, if `TRANSITIONS` allows it.
+    ///
+    /// In the `Submission` phase the new state reflects what GitHub told us,
+    /// so we update the repository's `archived` flag to match it. Otherwise
+    /// the new state has to agree with the flag we already have, and the
+    /// change is recorded so it can be undone.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TransitionError` (and leaves the map alone) if we don't know
+    /// about `key`, or if the change isn't allowed.
+    pub fn transition(&mut self, key: &RepoKey, to: DesiredState, phase: Phase) -> Result<&mut Self, TransitionError> {
+        let change = self.apply_transition(key, to, phase)?;
+        self.record(phase, change.into_iter().collect());
+        Ok(self)
+    }
+
+    /// Make a whole batch of changes as one undoable action, e.g., selecting
+    /// every repository on a page. Changes the table doesn't allow are skipped
+    /// rather than stopping the whole batch.
+    ///
+    /// Returns the errors for any changes we skipped.
+    pub fn transition_all(&mut self, changes: impl IntoIterator<Item = (RepoKey, DesiredState)>, phase: Phase) -> Vec<TransitionError> {
+        let mut applied = Vec::new();
+        let mut errors = Vec::new();
+        for (key, to) in changes {
+            match self.apply_transition(&key, to, phase) {
+                Ok(change) => applied.extend(change),
+                Err(err) => errors.push(err)
+            }
+        }
+        self.record(phase, applied);
+        errors
+    }
+
+    // Returns the change we made, or `None` if the repository was already in state `to`.
+    fn apply_transition(&mut self, key: &RepoKey, to: DesiredState, phase: Phase) -> Result<Option<Change>, TransitionError> {
+        let (repo, desired_state) = self.map.get_mut(key).ok_or_else(|| TransitionError::UnknownRepo(key.clone()))?;
+        let from = *desired_state;
+        if !from.can_transition_to(to, phase) {
+            return Err(TransitionError::Illegal { key: key.clone(), from, to, phase })
+        }
+        match (phase, to.required_archived_flag()) {
+            (Phase::Submission, Some(archived)) => repo.archived = archived,
+            (_, Some(archived)) if archived != repo.archived =>
+                return Err(TransitionError::WrongArchivedFlag { key: key.clone(), to, archived: repo.archived }),
+            _ => {}
+        }
+        *desired_state = to;
+        Ok((from != to).then(|| Change { key: key.clone(), from, to }))
+    }
+
+    // Only the user's own choices go in the history; we don't want undo to
+    // pretend GitHub hasn't archived something.
+    fn record(&mut self, phase: Phase, changes: Vec<Change>) {
+        if phase != Phase::Submission && !changes.is_empty() {
+            self.history.undo.push(changes);
+            self.history.redo.clear();
+        }
+    }
+
+    // Set each repository in `changes` back to `from` (or forward to `to`, if
+    // `forward`), skipping any that something else has changed since.
+    fn replay(&mut self, changes: &[Change], forward: bool) {
+        for change in changes {
+            let (expected, target) = if forward { (change.from, change.to) } else { (change.to, change.from) };
+            if let Some((_, desired_state)) = self.map.get_mut(&change.key) {
+                if *desired_state == expected {
+                    *desired_state = target;
+                }
+            }
+        }
+    }
+
+    #[must_use]
+    pub const fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    #[must_use]
+    pub const fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Undo the most recent action, returning `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(changes) = self.history.undo.pop() else {
+            return false
+        };
+        self.replay(&changes, false);
+        self.history.redo.push(changes);
+        true
+    }
+
+    /// Redo the most recently undone action, returning `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(changes) = self.history.redo.pop() else {
+            return false
+        };
+        self.replay(&changes, true);
+        self.history.undo.push(changes);
+        true
+    }
+
     /// # Panics
     ///
-    /// Will panic `repo_id` isn't in the `ArchiveStateMap`.    
+    /// Will panic `key` isn't in the `DesiredStateMap`.
     #[must_use]
-    pub fn get_repo(&self, repo_id: RepoId) -> &Repository {
-        assert!(self.map.contains_key(&repo_id), "Repository key {repo_id} not found in StateMap");
+    pub fn get_repo(&self, key: &RepoKey) -> &Repository {
+        assert!(self.map.contains_key(key), "Repository key {key} not found in StateMap");
         #[allow(clippy::unwrap_used)]
-        self.map.get(&repo_id).map(|p| &p.0).unwrap()
+        self.map.get(key).map(|p| &p.0).unwrap()
     }
 
-    pub fn get_repos_to_review(&self) -> impl Iterator<Item = &Repository> {
+    /// The repositories we've loaded from `organization`.
+    pub fn get_repos_in<'a>(&'a self, organization: &'a str) -> impl Iterator<Item = (&'a RepoKey, &'a Repository)> {
+        self.map.iter()
+            .filter(move |(key, _)| key.organization == organization)
+            .map(|(key, (repo, _))| (key, repo))
+    }
+
+    /// The repositories the review has anything to say about: those whose
+    /// state the review is allowed to change, and that can be selected in
+    /// this mode.
+    pub fn get_repos_to_review(&self, mode: SelectionMode) -> impl Iterator<Item = (&RepoKey, &Repository)> {
         self.map
-            .values()
-            .filter_map(|(repo, desired_state)| {
-                (*desired_state != DesiredState::AlreadyArchived || *desired_state != DesiredState::Keep)
-                    .then_some(repo)
+            .iter()
+            .filter_map(move |(key, (repo, desired_state))| {
+                (desired_state.has_transitions_in(Phase::Review) && mode.can_select(repo)).then_some((key, repo))
             })
     }
 
     #[must_use]
-    pub fn get_repo_ids_to_review(&self) -> Vec<RepoId> {
-        self.get_repos_to_review().map(|r| r.id).collect()
+    pub fn get_repo_keys_to_review(&self, mode: SelectionMode) -> Vec<RepoKey> {
+        self.get_repos_to_review(mode).map(|(key, _)| key.clone()).collect()
     }
 
     #[must_use]
-    pub fn get_owned_repos_to_review(&self) -> Vec<Repository> {
-        self.get_repos_to_review().cloned().collect()
+    pub fn get_owned_repos_to_review(&self, mode: SelectionMode) -> Vec<Repository> {
+        self.get_repos_to_review(mode).map(|(_, repo)| repo.clone()).collect()
     }
 
-    pub fn get_repos_to_archive(&self) -> impl Iterator<Item = &Repository> {
+    /// The repositories whose state can become `outcome` when we submit them.
+    fn get_repos_to_submit_for(&self, outcome: DesiredState) -> impl Iterator<Item = (&RepoKey, &Repository)> {
         self.map
-            .values()
-            .filter_map(|(repo, to_archive)| {
-                (*to_archive == DesiredState::Archive).then_some(repo)
+            .iter()
+            .filter_map(move |(key, (repo, desired_state))| {
+                TRANSITIONS.contains(&(Phase::Submission, *desired_state, outcome)).then_some((key, repo))
             })
     }
+
+    pub fn get_repos_to_archive(&self) -> impl Iterator<Item = (&RepoKey, &Repository)> {
+        self.get_repos_to_submit_for(DesiredState::Archived)
+    }
+
+    pub fn get_repos_to_unarchive(&self) -> impl Iterator<Item = (&RepoKey, &Repository)> {
+        self.get_repos_to_submit_for(DesiredState::Unarchived)
+    }
+
+    /// The repositories we need to send requests for in the given mode, in
+    /// order, so those from the same organization are together.
+    #[must_use]
+    pub fn get_owned_repos_to_change(&self, mode: SelectionMode) -> Vec<(RepoKey, Repository)> {
+        let owned = |(key, repo): (&RepoKey, &Repository)| (key.clone(), repo.clone());
+        match mode {
+            SelectionMode::Archive => self.get_repos_to_archive().map(owned).collect(),
+            SelectionMode::Unarchive => self.get_repos_to_unarchive().map(owned).collect(),
+        }
+    }
 }
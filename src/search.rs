@@ -0,0 +1,37 @@
+//! Searching the names and descriptions of every repository we've loaded,
+//! from every organization, without asking GitHub.
+//!
+//! The query is split into words, and a repository matches if each word
+//! appears somewhere in its name or description, ignoring case. Repositories
+//! with every word in their name (e.g., `lab-01-alice` when searching for
+//! `alice`) come first, since that's usually the one you're looking for.
+
+use crate::repository::{DesiredStateMap, RepoKey};
+
+/// Whether there's anything in `query` to search for.
+#[must_use]
+pub fn is_query(query: &str) -> bool {
+    !query.trim().is_empty()
+}
+
+/// The repositories in `state_map` that match `query`, best matches first,
+/// and otherwise in the order they're in `state_map`.
+#[must_use]
+pub fn search(state_map: &DesiredStateMap, query: &str) -> Vec<RepoKey> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Vec::new()
+    }
+    let mut results: Vec<(bool, &RepoKey)> = state_map.map.iter()
+        .filter_map(|(key, (repo, _))| {
+            let name = repo.name.to_lowercase();
+            let description = repo.description.as_deref().unwrap_or_default().to_lowercase();
+            terms.iter()
+                .all(|term| name.contains(term.as_str()) || description.contains(term.as_str()))
+                .then(|| (terms.iter().all(|term| name.contains(term.as_str())), key))
+        })
+        .collect();
+    // Stable, so the order within each kind of match is kept.
+    results.sort_by_key(|(in_name, _)| !in_name);
+    results.into_iter().map(|(_, key)| key.clone()).collect()
+}
@@ -1,12 +1,131 @@
-// TODO: Clean up the logging elsewhere to use `gloo::console::log`.
-use gloo::console::log;
+use std::fmt;
 
-use crate::repository::Repository;
+use crate::repository::{DesiredState, Repository, RepoKey};
+use crate::services::console;
+use crate::services::github_api::{ApiError, GitHubApi, RepoUpdate};
+use crate::services::retry::{FailureKind, RetryPolicy};
 
-pub fn archive_repositories<'a>(repos: impl Iterator<Item = &'a Repository>) {
-    for repo in repos {
-        // TODO: We need to change this to actually make the REST request
-        //  to the GitHub servers.
-        log!(format!("We are archiving {}.", repo.name));
+/// What happened when we asked GitHub to archive (or unarchive) a single repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveOutcome {
+    /// GitHub accepted the request and the repository is now archived.
+    Archived,
+    /// The repository was already archived, so there was nothing to do.
+    AlreadyArchived,
+    /// GitHub accepted the request and the repository is no longer archived.
+    Unarchived,
+    /// We were asked to unarchive a repository that wasn't archived.
+    NotArchived,
+    /// The logged in user isn't allowed to change this repository (HTTP 403).
+    PermissionDenied,
+    /// GitHub couldn't find the repository (HTTP 404). This is also what GitHub
+    /// returns for private repositories the user can't see.
+    NotFound,
+    /// A failure that will happen again no matter how often we retry, e.g.,
+    /// GitHub rejecting the request as invalid (HTTP 422).
+    Error(String),
+    /// A failure that might go away on its own (a network error, a 502, a
+    /// secondary rate limit), but that was still happening when we ran out of retries.
+    RetriesExhausted(String),
+}
+
+/// The outcome of archiving one repository, along with enough information
+/// about the repository for the caller to display it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveResult {
+    pub key: RepoKey,
+    /// The user or organization that owns the repository.
+    pub owner: String,
+    pub name: String,
+    pub outcome: ArchiveOutcome,
+    /// How many times we had to retry the request because of transient failures.
+    pub retries: u32,
+    /// Whether we sent GitHub a request at all. We don't if the repository
+    /// already has the `archived` flag we want.
+    pub sent_request: bool,
+    /// The HTTP status of GitHub's last response, if we got one.
+    pub status: Option<u16>,
+}
+
+impl ArchiveOutcome {
+    /// Whether the repository ended up in the state we wanted, regardless of who put it there.
+    #[must_use]
+    pub const fn succeeded(&self) -> bool {
+        matches!(self, Self::Archived | Self::AlreadyArchived | Self::Unarchived | Self::NotArchived)
+    }
+
+    /// The `DesiredState` a repository moves to when we get this outcome.
+    #[must_use]
+    pub const fn desired_state(&self) -> DesiredState {
+        match self {
+            Self::Archived => DesiredState::Archived,
+            Self::AlreadyArchived => DesiredState::AlreadyArchived,
+            Self::Unarchived => DesiredState::Unarchived,
+            Self::NotArchived => DesiredState::Keep,
+            Self::PermissionDenied | Self::NotFound | Self::Error(_) | Self::RetriesExhausted(_) => DesiredState::Failed,
+        }
+    }
+}
+
+impl fmt::Display for ArchiveOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Archived => write!(f, "Archived"),
+            Self::AlreadyArchived => write!(f, "Already archived"),
+            Self::Unarchived => write!(f, "Unarchived"),
+            Self::NotArchived => write!(f, "Wasn't archived"),
+            Self::PermissionDenied => write!(f, "Permission denied"),
+            Self::NotFound => write!(f, "Not found"),
+            Self::Error(message) => write!(f, "Error: {message}"),
+            Self::RetriesExhausted(message) => write!(f, "Gave up retrying: {message}"),
+        }
+    }
+}
+
+/// Ask GitHub to set the `archived` flag of a single repository, retrying
+/// transient failures according to `retry_policy`.
+#[allow(clippy::future_not_send)]
+pub async fn set_archived(api: &dyn GitHubApi, retry_policy: &RetryPolicy, key: &RepoKey, repo: &Repository, archived: bool) -> ArchiveResult {
+    let action = if archived { "archiving" } else { "unarchiving" };
+    // A user's repositories can belong to anyone who's added them as a collaborator.
+    let owner = repo.owner().unwrap_or_else(|| key.likely_owner());
+    console::log(&format!("We are {action} {}.", repo.name));
+    let (outcome, retries, status) = match (repo.archived, archived) {
+        (true, true) => (ArchiveOutcome::AlreadyArchived, 0, None),
+        (false, false) => (ArchiveOutcome::NotArchived, 0, None),
+        _ => {
+            let (result, retries) = retry_policy.run(|| {
+                api.update_repo(owner, &repo.name, RepoUpdate { archived: Some(archived) })
+            }).await;
+            let status = result.as_ref().map_or_else(ApiError::status, |_| Some(200));
+            (outcome_of(result, archived), retries, status)
+        }
+    };
+    console::log(&format!("The result of {action} {} was {outcome:?} after {retries} retries.", repo.name));
+    ArchiveResult {
+        key: key.clone(),
+        owner: owner.to_owned(),
+        name: repo.name.clone(),
+        outcome,
+        retries,
+        sent_request: repo.archived != archived,
+        status
+    }
+}
+
+/// What the response to a request to set the `archived` flag to `archived`
+/// means for the repository.
+#[must_use]
+pub fn outcome_of(result: Result<Repository, ApiError>, archived: bool) -> ArchiveOutcome {
+    match result {
+        Ok(_) if archived => ArchiveOutcome::Archived,
+        Ok(_) => ArchiveOutcome::Unarchived,
+        // GitHub refuses to modify archived repositories (they're read-only), so
+        // a 403 whose message mentions archiving means someone beat us to it.
+        Err(ApiError::Forbidden { message, .. }) if archived && message.contains("archived") => ArchiveOutcome::AlreadyArchived,
+        Err(ApiError::Forbidden { .. } | ApiError::Unauthorized(_)) => ArchiveOutcome::PermissionDenied,
+        Err(ApiError::NotFound(_)) => ArchiveOutcome::NotFound,
+        Err(err) if FailureKind::of(&err) == FailureKind::Transient => ArchiveOutcome::RetriesExhausted(err.to_string()),
+        Err(err) => ArchiveOutcome::Error(err.to_string())
     }
 }
@@ -0,0 +1,222 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use yewdux::prelude::*;
+
+use crate::repository::{DesiredState, RepoId, RepoKey, SelectionMode};
+use crate::services::archive_repos::ArchiveResult;
+use crate::services::github_api::RepoUpdate;
+
+/// Every batch in local storage has a key starting with this, followed by the
+/// time the batch started.
+const KEY_PREFIX: &str = "ice-repos:audit-log:";
+
+/// The request we sent GitHub for one repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRequest {
+    pub method: String,
+    pub path: String,
+    pub body: RepoUpdate,
+}
+
+/// What happened to one repository in a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub organization: String,
+    pub repo_id: RepoId,
+    pub repo_name: String,
+    /// `None` if we didn't need to send a request, or the batch was cancelled
+    /// before we got to this repository.
+    pub request: Option<AuditRequest>,
+    /// The HTTP status of GitHub's last response, if we got one.
+    pub status: Option<u16>,
+    pub outcome: String,
+    pub retries: u32,
+    /// The state the repository ended up in.
+    pub final_state: DesiredState,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    #[must_use]
+    pub fn from_result(mode: SelectionMode, result: &ArchiveResult, recorded_at: DateTime<Utc>) -> Self {
+        Self {
+            organization: result.key.organization.clone(),
+            repo_id: result.key.id,
+            repo_name: result.name.clone(),
+            request: result.sent_request.then(|| AuditRequest {
+                method: "PATCH".to_owned(),
+                path: format!("/repos/{}/{}", result.owner, result.name),
+                body: RepoUpdate { archived: Some(mode.target_archived()) },
+            }),
+            status: result.status,
+            outcome: result.outcome.to_string(),
+            retries: result.retries,
+            final_state: result.outcome.desired_state(),
+            recorded_at,
+        }
+    }
+
+    /// An entry for a repository the batch never got to.
+    #[must_use]
+    pub fn cancelled(key: &RepoKey, repo_name: String, final_state: DesiredState, recorded_at: DateTime<Utc>) -> Self {
+        Self {
+            organization: key.organization.clone(),
+            repo_id: key.id,
+            repo_name,
+            request: None,
+            status: None,
+            outcome: "Cancelled".to_owned(),
+            retries: 0,
+            final_state,
+            recorded_at,
+        }
+    }
+}
+
+/// One submitted batch of archive (or unarchive) requests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditBatch {
+    pub started_at: DateTime<Utc>,
+    /// `None` until the batch finishes, so a batch that's still `None` after
+    /// the page was closed was interrupted.
+    pub finished_at: Option<DateTime<Utc>>,
+    /// The GitHub user who submitted the batch, if we knew who they were.
+    pub login: Option<String>,
+    pub organizations: Vec<String>,
+    pub mode: SelectionMode,
+    /// The repositories submitted, as `organization/name`, in the order they were queued.
+    pub repos: Vec<String>,
+    /// What happened to each repository, in the order they finished.
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditBatch {
+    #[must_use]
+    pub const fn new(started_at: DateTime<Utc>, login: Option<String>, organizations: Vec<String>, mode: SelectionMode, repos: Vec<String>) -> Self {
+        Self {
+            started_at,
+            finished_at: None,
+            login,
+            organizations,
+            mode,
+            repos,
+            entries: Vec::new(),
+        }
+    }
+
+    fn storage_key(&self) -> String {
+        format!("{KEY_PREFIX}{}", self.started_at.to_rfc3339_opts(SecondsFormat::Millis, true))
+    }
+
+    fn write_markdown(&self, markdown: &mut String) {
+        let action = match self.mode {
+            SelectionMode::Archive => "Archive",
+            SelectionMode::Unarchive => "Unarchive",
+        };
+        let _ = writeln!(markdown, "## {action} batch for {}, started {}\n", self.organizations.join(", "), self.started_at.to_rfc3339());
+        let _ = writeln!(markdown, "- Submitted by: {}", self.login.as_deref().unwrap_or("unknown"));
+        let _ = writeln!(markdown, "- Finished: {}",
+            self.finished_at.map_or_else(|| "no (the batch was interrupted)".to_owned(), |at| at.to_rfc3339()));
+        let _ = writeln!(markdown, "- Repositories submitted: {}\n", self.repos.len());
+        markdown.push_str("| Organization | Repository | Request | Status | Outcome | Retries | Final state | Time |\n");
+        markdown.push_str("|---|---|---|---|---|---|---|---|\n");
+        for entry in &self.entries {
+            let request = entry.request.as_ref().map_or_else(
+                || "none".to_owned(),
+                |request| format!("`{} {} {}`", request.method, request.path, serde_json::to_string(&request.body).unwrap_or_default()));
+            let status = entry.status.map_or_else(|| "-".to_owned(), |status| status.to_string());
+            let _ = writeln!(markdown, "| {} | {} | {request} | {status} | {} | {} | {:?} | {} |",
+                escape_markdown_cell(&entry.organization),
+                escape_markdown_cell(&entry.repo_name),
+                escape_markdown_cell(&entry.outcome),
+                entry.retries,
+                entry.final_state,
+                entry.recorded_at.to_rfc3339());
+        }
+        markdown.push('\n');
+    }
+}
+
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Every batch that's been submitted from this browser, oldest first. The log
+/// itself lives in local storage; this store mirrors it for the UI.
+///
+/// The log is append-only: we add batches and entries, but never change or
+/// remove them.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store, Serialize, Deserialize)]
+pub struct AuditLog {
+    pub batches: Vec<AuditBatch>,
+}
+
+impl AuditLog {
+    /// Read the whole log out of local storage.
+    #[must_use]
+    pub fn load() -> Self {
+        let storage = LocalStorage::raw();
+        let mut batches: Vec<AuditBatch> = (0..LocalStorage::length())
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter(|key| key.starts_with(KEY_PREFIX))
+            .filter_map(|key| LocalStorage::get(key).ok())
+            .collect();
+        batches.sort_by_key(|batch| batch.started_at);
+        Self { batches }
+    }
+
+    /// Add a new batch to the log.
+    pub fn start_batch(batch: AuditBatch) {
+        save(&batch);
+        Dispatch::<Self>::new().reduce_mut(|log| log.batches.push(batch));
+    }
+
+    /// Add an entry to the batch that started at `started_at`.
+    pub fn append(started_at: DateTime<Utc>, entry: AuditEntry) {
+        Self::update_batch(started_at, |batch| batch.entries.push(entry));
+    }
+
+    pub fn finish_batch(started_at: DateTime<Utc>, finished_at: DateTime<Utc>) {
+        Self::update_batch(started_at, |batch| batch.finished_at = Some(finished_at));
+    }
+
+    fn update_batch(started_at: DateTime<Utc>, update: impl FnOnce(&mut AuditBatch)) {
+        Dispatch::<Self>::new().reduce_mut(|log| {
+            if let Some(batch) = log.batches.iter_mut().find(|batch| batch.started_at == started_at) {
+                update(batch);
+                save(batch);
+            }
+        });
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs with string keys can't fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# ice-repos audit log\n\n");
+        if self.batches.is_empty() {
+            markdown.push_str("No batches have been submitted.\n");
+        }
+        for batch in &self.batches {
+            batch.write_markdown(&mut markdown);
+        }
+        markdown
+    }
+}
+
+// Losing the audit log quietly would defeat the point of it, so if we can't
+// save we say so loudly.
+fn save(batch: &AuditBatch) {
+    if let Err(err) = LocalStorage::set(batch.storage_key(), batch) {
+        web_sys::console::error_1(&format!("Unable to save the audit log for the batch started {}: {err}", batch.started_at).into());
+        gloo::dialogs::alert(&format!("Unable to save the audit log for this batch: {err}. Download the log now if you need a record of it."));
+    }
+}
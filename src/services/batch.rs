@@ -0,0 +1,244 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use gloo::console::log;
+use yew::{Callback, Reducible};
+
+use crate::repository::{Repository, RepoKey, SelectionMode};
+use crate::services::archive_repos::{set_archived, ArchiveResult};
+use crate::services::github_api::GitHubApiHandle;
+use crate::services::retry::RetryPolicy;
+
+/// How many requests we have in flight at once unless told otherwise.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// The most requests we'll have in flight at once, so that a big batch
+/// doesn't set off GitHub's secondary rate limits.
+pub const MAX_CONCURRENCY: usize = 16;
+
+/// The progress reports a running batch sends back to the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchEvent {
+    /// A new batch is starting; any previous progress is out of date.
+    Started,
+    Queued { key: RepoKey, name: String },
+    InFlight(RepoKey),
+    Done(ArchiveResult),
+    Failed(ArchiveResult),
+    /// The batch was cancelled before we got to this repository.
+    Cancelled(RepoKey),
+    /// Every repository is done, failed, or cancelled.
+    Finished,
+    /// We got GitHub's answer for this repository, but couldn't record it,
+    /// e.g., because it was changed in the meantime. The executor never sends
+    /// this; it's for whoever handles its `Done` and `Failed` events.
+    NotRecorded { key: RepoKey, message: String },
+}
+
+/// A way for the UI to stop a running batch. Requests that are already in
+/// flight are allowed to finish, but nothing new is started.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Rc<Cell<bool>>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.set(true);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+impl PartialEq for CancelHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Runs a batch of archive (or unarchive) requests, at most `concurrency` at a time.
+pub struct BatchExecutor {
+    api: GitHubApiHandle,
+    retry_policy: RetryPolicy,
+    mode: SelectionMode,
+    concurrency: usize
+}
+
+impl BatchExecutor {
+    #[must_use]
+    pub fn new(api: GitHubApiHandle, concurrency: usize) -> Self {
+        Self {
+            api,
+            retry_policy: RetryPolicy::default(),
+            mode: SelectionMode::Archive,
+            concurrency: concurrency.clamp(1, MAX_CONCURRENCY)
+        }
+    }
+
+    #[must_use]
+    pub const fn with_mode(mut self, mode: SelectionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Start archiving (or unarchiving) `repos`, which can come from any number of
+    /// organizations, in the background, reporting progress through
+    /// `on_event`. The returned handle can be used to cancel the batch.
+    ///
+    /// We start `concurrency` workers that each pull repositories off a shared
+    /// queue until it's empty (or the batch is cancelled). The last worker to
+    /// stop reports anything left in the queue as cancelled and then sends `Finished`.
+    #[must_use]
+    pub fn run(self, repos: Vec<(RepoKey, Repository)>, on_event: &Callback<BatchEvent>) -> CancelHandle {
+        let cancel = CancelHandle::default();
+
+        on_event.emit(BatchEvent::Started);
+        for (key, repo) in &repos {
+            on_event.emit(BatchEvent::Queued { key: key.clone(), name: repo.name.clone() });
+        }
+
+        let workers = self.concurrency.min(repos.len());
+        if workers == 0 {
+            on_event.emit(BatchEvent::Finished);
+            return cancel
+        }
+
+        let queue = Rc::new(RefCell::new(VecDeque::from(repos)));
+        let active_workers = Rc::new(Cell::new(workers));
+        let executor = Rc::new(self);
+        for _ in 0..workers {
+            let queue = queue.clone();
+            let active_workers = active_workers.clone();
+            let executor = executor.clone();
+            let on_event = on_event.clone();
+            let cancel = cancel.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                loop {
+                    if cancel.is_cancelled() {
+                        break
+                    }
+                    // Don't hold the borrow across the `await` below or the
+                    // other workers won't be able to get at the queue.
+                    let next = queue.borrow_mut().pop_front();
+                    let Some((key, repo)) = next else { break };
+                    on_event.emit(BatchEvent::InFlight(key.clone()));
+                    let result = set_archived(&*executor.api, &executor.retry_policy, &key, &repo, executor.mode.target_archived()).await;
+                    on_event.emit(if result.outcome.succeeded() {
+                        BatchEvent::Done(result)
+                    } else {
+                        BatchEvent::Failed(result)
+                    });
+                }
+
+                active_workers.set(active_workers.get() - 1);
+                if active_workers.get() == 0 {
+                    for (key, _) in queue.borrow_mut().drain(..) {
+                        on_event.emit(BatchEvent::Cancelled(key));
+                    }
+                    log!("The batch has finished.");
+                    on_event.emit(BatchEvent::Finished);
+                }
+            });
+        }
+
+        cancel
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoProgress {
+    Queued,
+    InFlight,
+    Finished(ArchiveResult),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub key: RepoKey,
+    pub name: String,
+    pub progress: RepoProgress,
+    /// Why GitHub's answer couldn't be recorded, if it couldn't.
+    pub not_recorded: Option<String>,
+}
+
+/// Everything the UI needs to know about the most recent batch, built up
+/// from the `BatchEvent`s it has been sent.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BatchProgress {
+    pub entries: Vec<BatchEntry>,
+    pub running: bool,
+}
+
+impl BatchProgress {
+    pub fn apply(&mut self, event: BatchEvent) -> &mut Self {
+        match event {
+            BatchEvent::Started => {
+                self.entries.clear();
+                self.running = true;
+            },
+            BatchEvent::Queued { key, name } =>
+                self.entries.push(BatchEntry { key, name, progress: RepoProgress::Queued, not_recorded: None }),
+            BatchEvent::InFlight(key) => self.set_progress(&key, RepoProgress::InFlight),
+            BatchEvent::Done(result) | BatchEvent::Failed(result) =>
+                self.set_progress(&result.key.clone(), RepoProgress::Finished(result)),
+            BatchEvent::Cancelled(key) => self.set_progress(&key, RepoProgress::Cancelled),
+            BatchEvent::Finished => self.running = false,
+            BatchEvent::NotRecorded { key, message } => {
+                if let Some(entry) = self.entries.iter_mut().find(|e| e.key == key) {
+                    entry.not_recorded = Some(message);
+                }
+            },
+        }
+        self
+    }
+
+    fn set_progress(&mut self, key: &RepoKey, progress: RepoProgress) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| &e.key == key) {
+            entry.progress = progress;
+        }
+    }
+
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// How many repositories we're done with, whether they succeeded, failed, or were cancelled.
+    #[must_use]
+    pub fn completed(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.progress, RepoProgress::Finished(_) | RepoProgress::Cancelled))
+            .count()
+    }
+
+    #[must_use]
+    pub fn results(&self) -> Vec<ArchiveResult> {
+        self.entries
+            .iter()
+            .filter_map(|e| match &e.progress {
+                RepoProgress::Finished(result) => Some(result.clone()),
+                _ => None
+            })
+            .collect()
+    }
+}
+
+impl Reducible for BatchProgress {
+    type Action = BatchEvent;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut progress = (*self).clone();
+        progress.apply(action);
+        Rc::new(progress)
+    }
+}
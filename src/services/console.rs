@@ -0,0 +1,14 @@
+//! Logging to the browser's console.
+//!
+//! The console only exists in the browser, so anywhere else (i.e., in the
+//! tests) messages are dropped rather than panicking.
+
+/// Log `message` to the browser's console.
+#[cfg(target_arch = "wasm32")]
+pub fn log(message: &str) {
+    gloo::console::log!(message);
+}
+
+/// Log `message` to the browser's console.
+#[cfg(not(target_arch = "wasm32"))]
+pub const fn log(_message: &str) {}
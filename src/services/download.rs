@@ -0,0 +1,22 @@
+use gloo::file::{Blob, ObjectUrl};
+use gloo::timers::callback::Timeout;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlAnchorElement;
+
+/// Offer `contents` to the user as a file to save, the same way a link to a
+/// file on a server would.
+pub fn download(file_name: &str, mime_type: &str, contents: &str) {
+    let url = ObjectUrl::from(Blob::new_with_options(contents, Some(mime_type)));
+    let anchor = gloo::utils::document().create_element("a").ok()
+        .and_then(|element| element.dyn_into::<HtmlAnchorElement>().ok());
+    let Some(anchor) = anchor else {
+        web_sys::console::error_1(&format!("Unable to create a link to download {file_name}.").into());
+        return
+    };
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    // Some browsers start the download after `click` returns, so we hold on
+    // to the URL (which is revoked when it's dropped) a little longer.
+    Timeout::new(1_000, move || drop(url)).forget();
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use yewdux::prelude::*;
+
+/// Everything in local storage that belongs to the cache has a key starting with this,
+/// followed by the request URL.
+const KEY_PREFIX: &str = "ice-repos:etag-cache:";
+
+/// The most response body we keep in the cache, in bytes.
+///
+/// Local storage is typically 5MB, stores strings as UTF-16 (so this takes up
+/// twice as much), and is shared with saved sessions and the audit log, so we
+/// leave plenty of room.
+pub const MAX_BYTES: usize = 1024 * 1024;
+
+/// A response we've seen before, along with the `ETag` GitHub gave us for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub etag: String,
+    pub body: String,
+    // We need this to work out the number of pages when we serve a page from the cache.
+    pub link: Option<String>,
+    pub stored_at: DateTime<Utc>
+}
+
+/// One line in the summary of what's in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntrySummary {
+    pub url: String,
+    pub bytes: usize,
+    pub stored_at: DateTime<Utc>
+}
+
+/// What's currently in the cache, and how much use it's been this session. The
+/// cache itself lives in local storage; this is kept up to date so that the
+/// UI can show it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+pub struct EtagCacheSummary {
+    pub entries: Vec<CacheEntrySummary>,
+    // The number of requests this session that GitHub answered with a 304,
+    // so we served them from the cache.
+    pub hits: usize,
+    /// Whether `entries` has been read from local storage yet. Until it has,
+    /// it only knows about what we've cached this session.
+    pub loaded: bool
+}
+
+impl EtagCacheSummary {
+    #[must_use]
+    pub fn total_bytes(&self) -> usize {
+        self.entries.iter().map(|entry| entry.bytes).sum()
+    }
+
+    /// Note that `entry` is now in the cache, replacing anything for the same URL.
+    pub fn record(&mut self, entry: CacheEntrySummary) -> &mut Self {
+        self.forget(&entry.url);
+        self.entries.push(entry);
+        self
+    }
+
+    /// Note that nothing for `url` is in the cache any more.
+    pub fn forget(&mut self, url: &str) -> &mut Self {
+        self.entries.retain(|entry| entry.url != url);
+        self
+    }
+
+    /// Forget the oldest entries until there are no more than `max_bytes` of
+    /// them, returning the URLs of the ones that have to go.
+    pub fn evict(&mut self, max_bytes: usize) -> Vec<String> {
+        let mut evicted = Vec::new();
+        let mut total = self.total_bytes();
+        let mut oldest_first: Vec<&CacheEntrySummary> = self.entries.iter().collect();
+        oldest_first.sort_by_key(|entry| entry.stored_at);
+        for entry in oldest_first {
+            if total <= max_bytes {
+                break
+            }
+            total -= entry.bytes;
+            evicted.push(entry.url.clone());
+        }
+        self.entries.retain(|entry| !evicted.contains(&entry.url));
+        evicted
+    }
+}
+
+/// A cache of GET responses, keyed by request URL, kept in the browser's local
+/// storage so it survives reloads.
+///
+/// We send the stored `ETag` in an `If-None-Match` header, and if GitHub says
+/// nothing has changed (a 304) we use the stored body instead. 304s don't count
+/// against the rate limit, so going back over pages we've seen before is free.
+pub struct EtagCache;
+
+impl EtagCache {
+    #[must_use]
+    pub fn get(url: &str) -> Option<CachedResponse> {
+        LocalStorage::get(storage_key(url)).ok()
+    }
+
+    /// Remember a response, throwing away the oldest ones if the cache has
+    /// grown past `MAX_BYTES`. Local storage is fairly small (typically 5MB),
+    /// so if this fails we just carry on without caching this response.
+    pub fn store(url: &str, response: &CachedResponse) {
+        let dispatch = Dispatch::<EtagCacheSummary>::new();
+        if !dispatch.get().loaded {
+            refresh_summary();
+        }
+        if let Err(err) = LocalStorage::set(storage_key(url), response) {
+            web_sys::console::warn_1(&format!("Unable to cache the response for {url}: {err}").into());
+            return
+        }
+        let mut evicted = Vec::new();
+        dispatch.reduce_mut(|summary| {
+            summary.record(CacheEntrySummary { url: url.to_owned(), bytes: response.body.len(), stored_at: response.stored_at });
+            evicted = summary.evict(MAX_BYTES);
+        });
+        for url in evicted {
+            LocalStorage::delete(storage_key(&url));
+        }
+    }
+
+    /// Note that GitHub told us our cached copy of a response is still good.
+    pub fn record_hit() {
+        Dispatch::<EtagCacheSummary>::new().reduce_mut(|summary| summary.hits += 1);
+    }
+
+    /// The URLs of everything in the cache.
+    #[must_use]
+    pub fn urls() -> Vec<String> {
+        let storage = LocalStorage::raw();
+        (0..LocalStorage::length())
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter_map(|key| key.strip_prefix(KEY_PREFIX).map(ToOwned::to_owned))
+            .collect()
+    }
+
+    /// A summary of everything that's in the cache.
+    #[must_use]
+    pub fn entries() -> Vec<CacheEntrySummary> {
+        Self::urls()
+            .into_iter()
+            .filter_map(|url| {
+                Self::get(&url).map(|response| CacheEntrySummary {
+                    bytes: response.body.len(),
+                    stored_at: response.stored_at,
+                    url
+                })
+            })
+            .collect()
+    }
+
+    /// Throw away everything in the cache, leaving everything else in local storage alone.
+    pub fn clear() {
+        for url in Self::urls() {
+            LocalStorage::delete(storage_key(&url));
+        }
+        Dispatch::<EtagCacheSummary>::new().reduce_mut(|summary| {
+            summary.entries.clear();
+            summary.loaded = true;
+        });
+    }
+}
+
+fn storage_key(url: &str) -> String {
+    format!("{KEY_PREFIX}{url}")
+}
+
+/// Bring the `EtagCacheSummary` store back in line with what's in local storage.
+///
+/// This reads every cached response, so it's only worth doing once; after
+/// that the summary is kept up to date as responses are stored.
+pub fn refresh_summary() {
+    let entries = EtagCache::entries();
+    Dispatch::<EtagCacheSummary>::new().reduce_mut(|summary| {
+        summary.entries = entries;
+        summary.loaded = true;
+    });
+}
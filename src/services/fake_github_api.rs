@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use crate::page_repo_map::PageNumber;
+use crate::repository::Repository;
+use crate::services::github_api::{ApiError, ApiFuture, GitHubApi, RepoPage, RepoSource, RepoUpdate, Team, User};
+
+/// An in-memory stand-in for GitHub.
+///
+/// This lets us exercise the paginator and the review flow without a network
+/// connection (or an OAuth token). It tries to respond the way GitHub does,
+/// including refusing to modify archived repositories.
+pub struct FakeGitHubApi {
+    user: User,
+    // Map from the owner's login to that owner's repositories.
+    repos: RefCell<BTreeMap<String, Vec<Repository>>>,
+    // Map from an organization to its teams, and the names of the
+    // repositories each team has access to.
+    teams: BTreeMap<String, Vec<(Team, Vec<String>)>>
+}
+
+impl FakeGitHubApi {
+    #[must_use]
+    pub fn new(login: &str) -> Self {
+        Self {
+            user: User { login: login.to_owned() },
+            repos: RefCell::new(BTreeMap::new()),
+            teams: BTreeMap::new()
+        }
+    }
+
+    #[must_use]
+    pub fn with_org_repos(self, organization: &str, repos: Vec<Repository>) -> Self {
+        self.repos.borrow_mut()
+            .entry(organization.to_owned())
+            .or_default()
+            .extend(repos);
+        self
+    }
+
+    /// Add a team to `organization` with access to the named repositories.
+    #[must_use]
+    pub fn with_team(mut self, organization: &str, team: Team, repo_names: &[&str]) -> Self {
+        self.teams.entry(organization.to_owned())
+            .or_default()
+            .push((team, repo_names.iter().map(|name| (*name).to_owned()).collect()));
+        self
+    }
+
+    fn not_found() -> ApiError {
+        ApiError::NotFound("Not Found".to_owned())
+    }
+
+    fn find_repo(&self, owner: &str, name: &str) -> Result<Repository, ApiError> {
+        self.repos.borrow()
+            .get(owner)
+            .and_then(|repos| repos.iter().find(|r| r.name == name))
+            .cloned()
+            .ok_or_else(Self::not_found)
+    }
+}
+
+impl GitHubApi for FakeGitHubApi {
+    fn list_repos<'a>(&'a self, source: &'a RepoSource, page: PageNumber, per_page: u8) -> ApiFuture<'a, RepoPage> {
+        let repos = self.repos.borrow();
+        let found = match source {
+            RepoSource::Organization(owner) | RepoSource::User(owner) => repos.get(owner).cloned(),
+            RepoSource::Team { organization, team } => self.teams.get(organization)
+                .and_then(|teams| teams.iter().find(|(t, _)| t.slug == *team))
+                .map(|(_, names)| repos.get(organization)
+                    .map(|repos| repos.iter().filter(|r| names.contains(&r.name)).cloned().collect())
+                    .unwrap_or_default()),
+            // We don't keep track of collaborators, so anything the user doesn't
+            // own counts as shared with them.
+            RepoSource::AuthenticatedUser(affiliations) => Some(repos.iter()
+                .filter(|(owner, _)| if **owner == self.user.login { affiliations.owner } else { affiliations.collaborator })
+                .flat_map(|(_, repos)| repos.iter().cloned())
+                .collect())
+        };
+        let result = found
+            .ok_or_else(Self::not_found)
+            .map(|mut repos: Vec<Repository>| {
+                repos.sort_by_key(|r| r.pushed_at);
+                let per_page = usize::from(per_page.max(1));
+                let last_page = repos.len().div_ceil(per_page).max(1);
+                let repos = repos.into_iter()
+                    .skip(page.saturating_sub(1) * per_page)
+                    .take(per_page)
+                    .collect();
+                RepoPage { repos, last_page }
+            });
+        Box::pin(async move { result })
+    }
+
+    fn get_repo<'a>(&'a self, owner: &'a str, name: &'a str) -> ApiFuture<'a, Repository> {
+        let result = self.find_repo(owner, name);
+        Box::pin(async move { result })
+    }
+
+    fn update_repo<'a>(&'a self, owner: &'a str, name: &'a str, update: RepoUpdate) -> ApiFuture<'a, Repository> {
+        let mut repos = self.repos.borrow_mut();
+        let result = repos.get_mut(owner)
+            .and_then(|repos| repos.iter_mut().find(|r| r.name == name))
+            .ok_or_else(Self::not_found)
+            .and_then(|repo| {
+                if repo.archived && update.archived != Some(false) {
+                    return Err(ApiError::Forbidden {
+                        message: "Repository was archived so is read-only.".to_owned(),
+                        sso_url: None
+                    })
+                }
+                if let Some(archived) = update.archived {
+                    repo.archived = archived;
+                }
+                Ok(repo.clone())
+            });
+        Box::pin(async move { result })
+    }
+
+    fn current_user(&self) -> ApiFuture<'_, User> {
+        let user = self.user.clone();
+        Box::pin(async move { Ok(user) })
+    }
+
+    fn list_teams<'a>(&'a self, organization: &'a str) -> ApiFuture<'a, Vec<Team>> {
+        let result = if self.repos.borrow().contains_key(organization) {
+            Ok(self.teams.get(organization)
+                .map(|teams| teams.iter().map(|(team, _)| team.clone()).collect())
+                .unwrap_or_default())
+        } else {
+            Err(Self::not_found())
+        };
+        Box::pin(async move { result })
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use yewdux::store::Store;
+
+use crate::page_repo_map::PageNumber;
+use crate::repository::Repository;
+
+/// The futures returned by `GitHubApi` methods. These aren't `Send` because
+/// everything in the browser runs on a single thread, and boxing them lets
+/// us use the trait as a `dyn GitHubApi`.
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, ApiError>> + 'a>>;
+
+/// Everything that can go wrong talking to GitHub, split up by what we'd
+/// want to tell the user about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// We never got a response from GitHub.
+    Network(String),
+    /// A 404, which for listing usually means the organization (or user) name is
+    /// misspelled, or it's private and we can't see it.
+    NotFound(String),
+    /// A 401: GitHub didn't accept our token, e.g., because it's expired or been revoked.
+    Unauthorized(String),
+    /// A 403 that isn't about rate limiting. If the organization uses SAML single
+    /// sign-on and our token hasn't been authorized for it, `sso_url` is where the
+    /// user can go to authorize it.
+    Forbidden { message: String, sso_url: Option<String> },
+    /// A 429, or one of GitHub's secondary rate limit 403s. `retry_after` is the
+    /// number of seconds GitHub asked us to wait, if it sent a `Retry-After` header.
+    RateLimited { status: u16, message: String, retry_after: Option<u64> },
+    /// Any other non-success status, e.g., a 422 or a 502.
+    Http { status: u16, message: String, retry_after: Option<u64> },
+    /// GitHub responded with a body that we couldn't make sense of.
+    MalformedBody(String),
+    /// GitHub's GraphQL API responded successfully, but reported errors
+    /// in the body of the response.
+    GraphQl(String),
+}
+
+impl ApiError {
+    /// Sort a non-success response from GitHub into the right kind of error.
+    /// `sso_header` is the value of the `X-GitHub-SSO` header, if there was one.
+    #[must_use]
+    pub fn from_response(status: u16, message: String, retry_after: Option<u64>, sso_header: Option<&str>) -> Self {
+        match status {
+            401 => Self::Unauthorized(message),
+            404 => Self::NotFound(message),
+            429 => Self::RateLimited { status, message, retry_after },
+            // GitHub's secondary rate limits (e.g., for making lots of changes
+            // quickly) come back as 403s, which we'd otherwise treat as
+            // permission problems.
+            403 if retry_after.is_some() || message.to_lowercase().contains("secondary rate limit") =>
+                Self::RateLimited { status, message, retry_after },
+            403 => Self::Forbidden { message, sso_url: sso_header.and_then(parse_sso_url) },
+            _ => Self::Http { status, message, retry_after }
+        }
+    }
+
+    /// The HTTP status GitHub responded with, if we got a response.
+    #[must_use]
+    pub const fn status(&self) -> Option<u16> {
+        match self {
+            Self::NotFound(_) => Some(404),
+            Self::Unauthorized(_) => Some(401),
+            Self::Forbidden { .. } => Some(403),
+            Self::RateLimited { status, .. } | Self::Http { status, .. } => Some(*status),
+            // GraphQL errors, and bodies we can't parse, come with a 200.
+            Self::MalformedBody(_) | Self::GraphQl(_) => Some(200),
+            Self::Network(_) => None
+        }
+    }
+
+    /// The number of seconds GitHub asked us to wait before trying again, if any.
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited { retry_after, .. } | Self::Http { retry_after, .. } => *retry_after,
+            _ => None
+        }
+    }
+}
+
+/// The `X-GitHub-SSO` header looks like `required; url=https://github.com/orgs/...`.
+fn parse_sso_url(header: &str) -> Option<String> {
+    header
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix("url="))
+        .map(ToOwned::to_owned)
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Network(message) => write!(f, "network error: {message}"),
+            Self::NotFound(message) => write!(f, "not found: {message}"),
+            Self::Unauthorized(message) => write!(f, "unauthorized: {message}"),
+            Self::Forbidden { message, .. } => write!(f, "forbidden: {message}"),
+            Self::RateLimited { message, .. } => write!(f, "rate limited: {message}"),
+            Self::Http { status, message, .. } => write!(f, "HTTP {status}: {message}"),
+            Self::MalformedBody(message) => write!(f, "malformed response: {message}"),
+            Self::GraphQl(message) => write!(f, "GraphQL error: {message}"),
+        }
+    }
+}
+
+/// One page of repositories, along with the number of the last page
+/// so the caller knows how far it can go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoPage {
+    pub repos: Vec<Repository>,
+    pub last_page: PageNumber,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct User {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Team {
+    pub name: String,
+    /// The team's name as it appears in URLs.
+    pub slug: String,
+}
+
+/// The fields of a repository we might want to change. Any field
+/// that's `None` is left alone by GitHub.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+}
+
+/// Which of the logged in user's repositories to list, using GitHub's
+/// `affiliation` filter.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Affiliations {
+    /// Repositories the user owns.
+    pub owner: bool,
+    /// Repositories the user has been added to as a collaborator.
+    pub collaborator: bool,
+    /// Repositories the user can get to through an organization they're in.
+    pub organization_member: bool,
+}
+
+// GitHub's own default is all three, which tends to bury personal
+// repositories under organization ones, so we leave organizations out.
+impl Default for Affiliations {
+    fn default() -> Self {
+        Self { owner: true, collaborator: true, organization_member: false }
+    }
+}
+
+impl Affiliations {
+    /// The affiliations that are turned on, using the REST API's names.
+    #[must_use]
+    pub fn names(self) -> Vec<&'static str> {
+        [(self.owner, "owner"), (self.collaborator, "collaborator"), (self.organization_member, "organization_member")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .collect()
+    }
+}
+
+/// Where to list repositories from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RepoSource {
+    Organization(String),
+    /// The repositories the logged in user can get to (`/user/repos`).
+    AuthenticatedUser(Affiliations),
+    /// Another user's public repositories, or the logged in user's own.
+    User(String),
+    /// The repositories a team in an organization has access to. `team` is
+    /// the team's slug.
+    Team { organization: String, team: String },
+}
+
+impl RepoSource {
+    /// What we call the logged in user's repositories in tabs and headings.
+    /// GitHub logins can't contain `@`, so this can't clash with an
+    /// organization or user.
+    pub const AUTHENTICATED_USER_NAME: &'static str = "@me";
+
+    /// The name we file this source's repositories under. Organizations and
+    /// users share a namespace on GitHub, so their names can't clash, and
+    /// teams are `organization/team`, which no organization can be.
+    #[must_use]
+    pub fn name(&self) -> String {
+        match self {
+            Self::Organization(name) | Self::User(name) => name.clone(),
+            Self::AuthenticatedUser(_) => Self::AUTHENTICATED_USER_NAME.to_owned(),
+            Self::Team { organization, team } => format!("{organization}/{team}"),
+        }
+    }
+
+    /// A description of the source to use in sentences, e.g., "the organization example".
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Organization(name) => format!("the organization {name}"),
+            Self::AuthenticatedUser(affiliations) => format!("your account ({})", affiliations.names().join(", ").replace('_', " ")),
+            Self::User(name) => format!("the user {name}"),
+            Self::Team { organization, team } => format!("the team {team} in {organization}"),
+        }
+    }
+}
+
+/// The subset of the GitHub API that ice-repos uses.
+///
+/// Components should only talk to GitHub through this trait (via
+/// `use_github_api`), so that we can swap in `FakeGitHubApi` when we
+/// don't want to touch the network.
+pub trait GitHubApi {
+    /// Get one page of the repositories from `source`, sorted so the ones
+    /// pushed to longest ago come first.
+    fn list_repos<'a>(&'a self, source: &'a RepoSource, page: PageNumber, per_page: u8) -> ApiFuture<'a, RepoPage>;
+
+    fn get_repo<'a>(&'a self, owner: &'a str, name: &'a str) -> ApiFuture<'a, Repository>;
+
+    /// Apply `update` to the given repository, returning the updated repository.
+    fn update_repo<'a>(&'a self, owner: &'a str, name: &'a str, update: RepoUpdate) -> ApiFuture<'a, Repository>;
+
+    /// The user we're authenticated as.
+    fn current_user(&self) -> ApiFuture<'_, User>;
+
+    /// All the teams in `organization` that the logged in user can see.
+    fn list_teams<'a>(&'a self, organization: &'a str) -> ApiFuture<'a, Vec<Team>>;
+}
+
+/// Which of GitHub's APIs we use to list repositories. Everything else
+/// (archiving, etc.) always goes through the REST API.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Store, Serialize, Deserialize)]
+pub enum ListingBackend {
+    #[default]
+    Rest,
+    GraphQl,
+}
+
+/// A shareable handle to some `GitHubApi` implementation, which is what we
+/// pass around in Yew's context. Two handles are equal if they point at the
+/// same client.
+#[derive(Clone)]
+pub struct GitHubApiHandle(pub Rc<dyn GitHubApi>);
+
+impl GitHubApiHandle {
+    pub fn new(api: impl GitHubApi + 'static) -> Self {
+        Self(Rc::new(api))
+    }
+}
+
+impl PartialEq for GitHubApiHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for GitHubApiHandle {
+    type Target = dyn GitHubApi;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
@@ -0,0 +1,348 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+
+use gloo::console::log;
+use reqwasm::http::Request;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::page_repo_map::PageNumber;
+use crate::repository::{RepoId, RepoPermissions, Repository, Visibility};
+use crate::services::github_api::{ApiError, ApiFuture, GitHubApi, RepoPage, RepoSource, RepoUpdate, Team, User};
+use crate::services::rest_client::RestClient;
+
+/// Only ask for the handful of fields that `Repository` actually uses.
+/// `{VARIABLES}`, `{OWNER}`, `{FILTERS}`, and `{CLOSE}` are filled in by `repos_query`,
+/// depending on where the repositories come from.
+const REPOS_QUERY: &str = "
+query($first: Int!, $after: String{VARIABLES}) {
+  {OWNER}
+    repositories(first: $first, after: $after, orderBy: {field: PUSHED_AT, direction: ASC}{FILTERS}) {
+      totalCount
+      pageInfo { endCursor hasNextPage }
+      nodes {
+        databaseId name nameWithOwner description isArchived updatedAt pushedAt
+        isFork isPrivate visibility isTemplate url diskUsage viewerPermission
+        repositoryTopics(first: 20) { nodes { topic { name } } }
+        primaryLanguage { name }
+        defaultBranchRef { name }
+        issues(states: OPEN) { totalCount }
+        pullRequests(states: OPEN) { totalCount }
+      }
+    }
+  }{CLOSE}
+}";
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlError>>
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    #[serde(rename = "type")]
+    error_type: Option<String>,
+    message: String
+}
+
+/// The query for `source`, and the string variables to pass it.
+///
+/// The logged in user is `viewer` rather than `user(login: ...)`, and GraphQL rejects
+/// queries that declare variables they don't use, so we only declare the ones
+/// that are needed. A team's repositories are one level further down, inside
+/// its organization.
+#[must_use]
+pub fn repos_query(source: &RepoSource) -> (String, Vec<(&'static str, &str)>) {
+    let (owner, filters, variables) = match source {
+        RepoSource::Organization(login) =>
+            ("owner: organization(login: $login) {", String::new(), vec![("login", login.as_str())]),
+        RepoSource::User(login) =>
+            ("owner: user(login: $login) {", ", ownerAffiliations: [OWNER]".to_owned(), vec![("login", login.as_str())]),
+        RepoSource::AuthenticatedUser(affiliations) => {
+            let affiliations = affiliations.names().join(", ").to_uppercase();
+            ("owner: viewer {", format!(", affiliations: [{affiliations}], ownerAffiliations: [{affiliations}]"), vec![])
+        }
+        RepoSource::Team { organization, team } =>
+            ("organization(login: $login) { owner: team(slug: $team) {", String::new(),
+             vec![("login", organization.as_str()), ("team", team.as_str())]),
+    };
+    let declarations = variables.iter().fold(String::new(), |mut declarations, (name, _)| {
+        let _ = write!(declarations, ", ${name}: String!");
+        declarations
+    });
+    let close = if matches!(source, RepoSource::Team { .. }) { "}" } else { "" };
+    let query = REPOS_QUERY
+        .replace("{VARIABLES}", &declarations)
+        .replace("{OWNER}", owner)
+        .replace("{FILTERS}", &filters)
+        .replace("{CLOSE}", close);
+    (query, variables)
+}
+
+#[derive(Deserialize)]
+struct ReposData {
+    // Whichever of `organization`, `user`, or `viewer` we asked for.
+    owner: Option<OwnerRepos>,
+    // For a team, the organization it's in.
+    organization: Option<TeamOrganization>
+}
+
+#[derive(Deserialize)]
+struct TeamOrganization {
+    owner: Option<OwnerRepos>
+}
+
+#[derive(Deserialize)]
+struct OwnerRepos {
+    repositories: RepositoryConnection
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryConnection {
+    total_count: usize,
+    page_info: PageInfo,
+    nodes: Vec<RepositoryNode>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    end_cursor: Option<String>,
+    has_next_page: bool
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryNode {
+    database_id: RepoId,
+    name: String,
+    name_with_owner: String,
+    description: Option<String>,
+    is_archived: bool,
+    updated_at: DateTime<Local>,
+    // This is null for repositories that have never been pushed to.
+    pushed_at: Option<DateTime<Local>>,
+    is_fork: bool,
+    is_private: bool,
+    visibility: Visibility,
+    is_template: bool,
+    url: String,
+    // In kilobytes, like the REST API's `size`. Null if GitHub hasn't worked it out yet.
+    disk_usage: Option<u64>,
+    viewer_permission: Option<String>,
+    repository_topics: Connection<TopicNode>,
+    primary_language: Option<Named>,
+    // Null for empty repositories.
+    default_branch_ref: Option<Named>,
+    issues: Count,
+    pull_requests: Count
+}
+
+#[derive(Deserialize)]
+struct Connection<T> {
+    nodes: Vec<T>
+}
+
+#[derive(Deserialize)]
+struct TopicNode {
+    topic: Named
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: String
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Count {
+    total_count: u32
+}
+
+/// GraphQL gives us the user's role, where REST gives us the set of
+/// things that role allows.
+fn permissions_for(role: &str) -> RepoPermissions {
+    let rank = match role {
+        "ADMIN" => 4,
+        "MAINTAIN" => 3,
+        "WRITE" => 2,
+        "TRIAGE" => 1,
+        _ => 0
+    };
+    RepoPermissions {
+        admin: rank >= 4,
+        maintain: rank >= 3,
+        push: rank >= 2,
+        triage: rank >= 1,
+        pull: true
+    }
+}
+
+impl From<RepositoryNode> for Repository {
+    fn from(node: RepositoryNode) -> Self {
+        Self {
+            id: node.database_id,
+            name: node.name,
+            full_name: Some(node.name_with_owner),
+            description: node.description,
+            archived: node.is_archived,
+            updated_at: node.updated_at,
+            pushed_at: node.pushed_at.unwrap_or(node.updated_at),
+            fork: node.is_fork,
+            private: node.is_private,
+            visibility: Some(node.visibility),
+            is_template: node.is_template,
+            topics: node.repository_topics.nodes.into_iter().map(|node| node.topic.name).collect(),
+            language: node.primary_language.map(|language| language.name),
+            size: node.disk_usage.unwrap_or(0),
+            default_branch: node.default_branch_ref.map(|branch| branch.name).unwrap_or_default(),
+            // The REST API counts open pull requests as issues, so we do the same.
+            open_issues_count: node.issues.total_count + node.pull_requests.total_count,
+            html_url: node.url,
+            permissions: node.viewer_permission.as_deref().map(permissions_for)
+        }
+    }
+}
+
+/// Lists repositories through GitHub's GraphQL API, which lets us ask for just
+/// the fields we need instead of the very large REST representation.
+///
+/// GraphQL pages with opaque cursors rather than page numbers, so we remember
+/// the cursor that each page ends with, and use that to fetch the next one.
+/// Everything other than listing is handed off to the REST API.
+pub struct GraphQlClient {
+    rest: RestClient,
+    // Map from (source, page number) to the cursor that page starts after.
+    // Page 1 doesn't need a cursor, so it's never in here.
+    cursors: RefCell<HashMap<(RepoSource, PageNumber), String>>
+}
+
+impl GraphQlClient {
+    #[must_use]
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self {
+            rest: RestClient::new(access_token),
+            cursors: RefCell::new(HashMap::new())
+        }
+    }
+
+    fn cursor_for(&self, source: &RepoSource, page: PageNumber) -> Option<String> {
+        self.cursors.borrow().get(&(source.clone(), page)).cloned()
+    }
+
+    #[allow(clippy::future_not_send)]
+    async fn fetch_page(&self, source: &RepoSource, page: PageNumber, per_page: u8, after: Option<String>) -> Result<RepoPage, ApiError> {
+        let started = Utc::now();
+        let (query, strings) = repos_query(source);
+        let mut variables = json!({ "first": per_page, "after": after });
+        for (name, value) in strings {
+            variables[name] = json!(value);
+        }
+        let body = json!({ "query": query, "variables": variables }).to_string();
+        let response = RestClient::send(|| self.rest.authorize(Request::post("/graphql")).body(body.clone())).await?;
+        let text = RestClient::read_body(&response).await?;
+        let (repo_page, next_cursor) = parse_repos_page(source, &text, per_page)?;
+        log!(format!("Loaded page {page} over GraphQL: {} bytes in {}ms.",
+            text.len(), (Utc::now() - started).num_milliseconds()));
+
+        if let Some(next_cursor) = next_cursor {
+            self.cursors.borrow_mut().insert((source.clone(), page + 1), next_cursor);
+        }
+        Ok(repo_page)
+    }
+}
+
+/// Turn GitHub's response to the query from `repos_query` into a page of
+/// repositories, along with the cursor the next page starts after (if
+/// there is a next page).
+///
+/// # Errors
+///
+/// Returns an `ApiError` if the response isn't what we asked for, reports
+/// GraphQL errors, or doesn't include the owner (i.e., it doesn't exist).
+pub fn parse_repos_page(source: &RepoSource, text: &str, per_page: u8) -> Result<(RepoPage, Option<String>), ApiError> {
+    let GraphQlResponse { data, errors } = RestClient::parse_json::<GraphQlResponse<ReposData>>(text)?;
+    if let Some(errors) = errors.filter(|errors| !errors.is_empty()) {
+        return Err(graphql_error(&errors))
+    }
+    let connection = data
+        .and_then(|data| data.owner.or_else(|| data.organization.and_then(|organization| organization.owner)))
+        .ok_or_else(|| ApiError::NotFound(format!("Couldn't find {}", source.describe())))?
+        .repositories;
+    let next_cursor = connection.page_info.end_cursor.filter(|_| connection.page_info.has_next_page);
+    let last_page = connection.total_count.div_ceil(usize::from(per_page.max(1))).max(1);
+    let repo_page = RepoPage {
+        repos: connection.nodes.into_iter().map(Repository::from).collect(),
+        last_page
+    };
+    Ok((repo_page, next_cursor))
+}
+
+fn graphql_error(errors: &[GraphQlError]) -> ApiError {
+    let message = errors.iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let has_type = |error_type| errors.iter().any(|e| e.error_type.as_deref() == Some(error_type));
+    if has_type("NOT_FOUND") {
+        ApiError::NotFound(message)
+    } else if has_type("FORBIDDEN") {
+        ApiError::Forbidden { message, sso_url: None }
+    } else if has_type("RATE_LIMITED") {
+        ApiError::RateLimited { status: 200, message, retry_after: None }
+    } else {
+        ApiError::GraphQl(message)
+    }
+}
+
+impl GitHubApi for GraphQlClient {
+    fn list_repos<'a>(&'a self, source: &'a RepoSource, page: PageNumber, per_page: u8) -> ApiFuture<'a, RepoPage> {
+        Box::pin(async move {
+            if page <= 1 {
+                return self.fetch_page(source, 1, per_page, None).await
+            }
+            if let Some(cursor) = self.cursor_for(source, page) {
+                return self.fetch_page(source, page, per_page, Some(cursor)).await
+            }
+            // We can't jump straight to a page we don't have a cursor for, so walk
+            // forward from the furthest page we do know how to get to.
+            let mut start = (2..page).rev()
+                .find(|p| self.cursor_for(source, *p).is_some())
+                .unwrap_or(1);
+            loop {
+                let after = self.cursor_for(source, start);
+                let repo_page = self.fetch_page(source, start, per_page, after).await?;
+                if start == page {
+                    return Ok(repo_page)
+                }
+                // Like the REST API, there's nothing on the pages past the last one.
+                if start >= repo_page.last_page {
+                    return Ok(RepoPage { repos: Vec::new(), last_page: repo_page.last_page })
+                }
+                start += 1;
+            }
+        })
+    }
+
+    fn get_repo<'a>(&'a self, owner: &'a str, name: &'a str) -> ApiFuture<'a, Repository> {
+        self.rest.get_repo(owner, name)
+    }
+
+    fn update_repo<'a>(&'a self, owner: &'a str, name: &'a str, update: RepoUpdate) -> ApiFuture<'a, Repository> {
+        self.rest.update_repo(owner, name, update)
+    }
+
+    fn list_teams<'a>(&'a self, organization: &'a str) -> ApiFuture<'a, Vec<Team>> {
+        self.rest.list_teams(organization)
+    }
+
+    fn current_user(&self) -> ApiFuture<'_, User> {
+        self.rest.current_user()
+    }
+}
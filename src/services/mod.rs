@@ -1 +1,13 @@
-pub mod archive_repos;
\ No newline at end of file
+pub mod archive_repos;
+pub mod github_api;
+pub mod rest_client;
+pub mod graphql_client;
+pub mod fake_github_api;
+pub mod rate_limit;
+pub mod etag_cache;
+pub mod retry;
+pub mod batch;
+pub mod session;
+pub mod download;
+pub mod audit_log;
+pub mod console;
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use gloo::console::log;
+use gloo::timers::future::TimeoutFuture;
+
+use reqwasm::http::Headers;
+
+use yewdux::prelude::*;
+
+/// How many requests' worth of budget we hold back. Several requests can be
+/// in flight at once, each having checked the budget before the others got
+/// their responses, so we stop a little short of zero.
+pub const RESERVE: u32 = 10;
+
+/// What GitHub has told us about our remaining API budget, taken from
+/// the `X-RateLimit-*` headers of the most recent response.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+pub struct RateLimit {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    /// When GitHub will give us a fresh budget.
+    pub reset: Option<DateTime<Utc>>,
+    /// If we're holding requests back until the reset, this is when
+    /// we expect to start sending them again.
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    /// Update the budget from the rate limit headers in a response. Any
+    /// header that's missing or unparseable leaves that value unchanged.
+    pub fn update_from_headers(&mut self, headers: &Headers) -> &mut Self {
+        self.update_from(|name| headers.get(name))
+    }
+
+    /// Update the budget from whatever `header` says the value of each rate
+    /// limit header is. This is `update_from_headers` for when there's no
+    /// browser to give us a `Headers`.
+    pub fn update_from(&mut self, header: impl Fn(&str) -> Option<String>) -> &mut Self {
+        let header = |name: &str| header(name).and_then(|value| value.trim().parse::<i64>().ok());
+        if let Some(limit) = header("x-ratelimit-limit").and_then(|l| u32::try_from(l).ok()) {
+            self.limit = Some(limit);
+        }
+        if let Some(remaining) = header("x-ratelimit-remaining").and_then(|r| u32::try_from(r).ok()) {
+            self.remaining = Some(remaining);
+        }
+        if let Some(reset) = header("x-ratelimit-reset").and_then(|r| Utc.timestamp_opt(r, 0).single()) {
+            self.reset = Some(reset);
+        }
+        self
+    }
+
+    #[must_use]
+    pub const fn is_exhausted(&self) -> bool {
+        matches!(self.remaining, Some(0))
+    }
+
+    /// Whether we're down to our last `RESERVE` requests.
+    #[must_use]
+    pub const fn is_low(&self) -> bool {
+        matches!(self.remaining, Some(remaining) if remaining <= RESERVE)
+    }
+
+    /// How long we need to wait before sending another request, or `None`
+    /// if there's still enough budget left (or the reset time has already passed).
+    #[must_use]
+    pub fn wait_time(&self, now: DateTime<Utc>) -> Option<Duration> {
+        if !self.is_low() {
+            return None
+        }
+        self.reset
+            .map(|reset| reset - now)
+            .filter(|wait| *wait > Duration::zero())
+    }
+}
+
+/// If we're down to our reserve, wait (without blocking the browser) until
+/// GitHub resets it. The `RateLimit` store's `paused_until` is set while
+/// we're waiting so the UI can say why nothing is happening.
+#[allow(clippy::future_not_send)]
+pub async fn wait_for_budget() {
+    let dispatch = Dispatch::<RateLimit>::new();
+    let now = Utc::now();
+    let Some(wait) = dispatch.get().wait_time(now) else {
+        return
+    };
+    log!(format!("Almost out of GitHub API budget; pausing requests for {} seconds.", wait.num_seconds()));
+    dispatch.reduce_mut(|rate_limit| rate_limit.paused_until = Some(now + wait));
+    // Wait an extra second so we don't beat GitHub's clock.
+    let millis = u32::try_from(wait.num_milliseconds() + 1000).unwrap_or(u32::MAX);
+    TimeoutFuture::new(millis).await;
+    dispatch.reduce_mut(|rate_limit| {
+        rate_limit.paused_until = None;
+        // We don't know the new budget until the next response tells us.
+        rate_limit.remaining = None;
+    });
+}
+
+/// Record the rate limit headers from a response in the `RateLimit` store.
+pub fn record_rate_limit(headers: &Headers) {
+    Dispatch::<RateLimit>::new().reduce_mut(|rate_limit| {
+        rate_limit.update_from_headers(headers);
+    });
+}
@@ -0,0 +1,211 @@
+use chrono::Utc;
+
+use gloo::console::log;
+use reqwasm::http::{Request, Response};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use yewdux::prelude::Dispatch;
+
+use crate::link_header::PaginationLinks;
+use crate::page_repo_map::PageNumber;
+use crate::repository::Repository;
+use crate::services::etag_cache::{CachedResponse, EtagCache};
+use crate::services::github_api::{ApiError, ApiFuture, GitHubApi, RepoPage, RepoSource, RepoUpdate, Team, User};
+use crate::services::rate_limit::{record_rate_limit, wait_for_budget, RateLimit};
+use crate::services::retry::parse_retry_after;
+
+/// The most GitHub allows.
+const TEAMS_PER_PAGE: u8 = 100;
+
+/// The body GitHub sends back along with error statuses.
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String
+}
+
+/// The real GitHub REST API, authenticated with the user's OAuth token.
+///
+/// All the URLs here are relative; in development they're forwarded to
+/// `api.github.com` by the proxies in `Trunk.toml`.
+pub struct RestClient {
+    access_token: String
+}
+
+impl RestClient {
+    #[must_use]
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self {
+            access_token: access_token.into()
+        }
+    }
+
+    pub(crate) fn authorize(&self, request: Request) -> Request {
+        request
+            .header("Accept", "application/vnd.github+json")
+            .header("Authorization", &format!("token {}", self.access_token))
+    }
+
+    /// Send the request, turning network failures and non-success
+    /// statuses into `ApiError`s. We take a function that builds the
+    /// request (rather than the request itself) because we may need to
+    /// send it again if we have to wait for our rate limit to reset.
+    #[allow(clippy::future_not_send)]
+    pub(crate) async fn send(make_request: impl Fn() -> Request) -> Result<Response, ApiError> {
+        loop {
+            wait_for_budget().await;
+            let response = make_request().send()
+                .await
+                .map_err(|err| ApiError::Network(err.to_string()))?;
+            record_rate_limit(&response.headers());
+            let status = response.status();
+            // A 304 is only ever an answer to a conditional request, and it's up
+            // to whoever sent that to fill in the body from their cache.
+            if response.ok() || status == 304 {
+                return Ok(response)
+            }
+            // GitHub responds with a 403 (or sometimes a 429) when we're out of
+            // budget. Now that we've recorded that, go around again and
+            // `wait_for_budget` will hold this request until the reset.
+            let rate_limit = Dispatch::<RateLimit>::new().get();
+            let out_of_budget = rate_limit.is_exhausted() && rate_limit.wait_time(Utc::now()).is_some();
+            if matches!(status, 403 | 429) && out_of_budget {
+                continue
+            }
+            let retry_after = response.headers()
+                .get("retry-after")
+                .and_then(|value| parse_retry_after(&value, Utc::now()));
+            let sso_header = response.headers().get("x-github-sso");
+            let message = match response.json::<ErrorBody>().await {
+                Ok(ErrorBody { message }) => message,
+                Err(_) => response.status_text()
+            };
+            return Err(ApiError::from_response(status, message, retry_after, sso_header.as_deref()))
+        }
+    }
+
+    #[allow(clippy::future_not_send)]
+    pub(crate) async fn read_body(response: &Response) -> Result<String, ApiError> {
+        response.text()
+            .await
+            .map_err(|err| ApiError::Network(err.to_string()))
+    }
+
+    pub(crate) fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+        serde_json::from_str(body)
+            .map_err(|err| ApiError::MalformedBody(err.to_string()))
+    }
+
+    #[allow(clippy::future_not_send)]
+    async fn parse_body<T: DeserializeOwned>(response: &Response) -> Result<T, ApiError> {
+        Self::parse_json(&Self::read_body(response).await?)
+    }
+}
+
+/// The URL for one page of the repositories from `source`.
+#[must_use]
+pub fn list_repos_url(source: &RepoSource, page: PageNumber, per_page: u8) -> String {
+    let path = match source {
+        RepoSource::Organization(organization) => format!("/orgs/{organization}/repos?"),
+        RepoSource::AuthenticatedUser(affiliations) => format!("/user/repos?affiliation={}&", affiliations.names().join(",")),
+        RepoSource::User(user) => format!("/users/{user}/repos?"),
+        RepoSource::Team { organization, team } => format!("/orgs/{organization}/teams/{team}/repos?"),
+    };
+    // GitHub doesn't sort team repositories, so they come back in its default order.
+    let sort = if matches!(source, RepoSource::Team { .. }) { "" } else { "sort=pushed&direction=asc&" };
+    format!("{path}{sort}per_page={per_page}&page={page}")
+}
+
+impl GitHubApi for RestClient {
+    fn list_repos<'a>(&'a self, source: &'a RepoSource, page: PageNumber, per_page: u8) -> ApiFuture<'a, RepoPage> {
+        Box::pin(async move {
+            let started = Utc::now();
+            let request_url = list_repos_url(source, page, per_page);
+            let cached = EtagCache::get(&request_url);
+            let response = Self::send(|| {
+                let request = self.authorize(Request::get(&request_url));
+                match &cached {
+                    Some(cached) => request.header("If-None-Match", &cached.etag),
+                    None => request
+                }
+            }).await?;
+            let (body, link, source) = match cached {
+                Some(cached) if response.status() == 304 => {
+                    EtagCache::record_hit();
+                    (cached.body, cached.link, "the ETag cache")
+                }
+                _ => {
+                    let link = response.headers().get("link");
+                    let body = Self::read_body(&response).await?;
+                    if let Some(etag) = response.headers().get("etag") {
+                        EtagCache::store(&request_url, &CachedResponse {
+                            etag,
+                            body: body.clone(),
+                            link: link.clone(),
+                            stored_at: Utc::now()
+                        });
+                    }
+                    (body, link, "REST")
+                }
+            };
+            web_sys::console::log_1(&format!("The link element of the header was <{link:?}>.").into());
+            let last_page = match link.as_deref() {
+                None => 1,
+                Some(link_str) => PaginationLinks::parse(link_str)
+                    .map_err(|err| ApiError::MalformedBody(format!("Unable to parse the link header: {err}")))?
+                    .last_page(page)
+            };
+            // This seems fairly slow when there are a lot of repositories. My guess
+            // is that parsing the huge pile of JSON we get back is at least part of the
+            // problem. `GraphQlClient` only asks for the fields we need; compare the
+            // numbers logged here with the ones it logs.
+            let repos = Self::parse_json(&body)?;
+            log!(format!("Loaded page {page} over {source}: {} bytes in {}ms.",
+                body.len(), (Utc::now() - started).num_milliseconds()));
+            Ok(RepoPage { repos, last_page })
+        })
+    }
+
+    fn get_repo<'a>(&'a self, owner: &'a str, name: &'a str) -> ApiFuture<'a, Repository> {
+        Box::pin(async move {
+            let request_url = format!("/repos/{owner}/{name}");
+            let response = Self::send(|| self.authorize(Request::get(&request_url))).await?;
+            Self::parse_body(&response).await
+        })
+    }
+
+    fn update_repo<'a>(&'a self, owner: &'a str, name: &'a str, update: RepoUpdate) -> ApiFuture<'a, Repository> {
+        Box::pin(async move {
+            let request_url = format!("/repos/{owner}/{name}");
+            let body = serde_json::to_string(&update)
+                .map_err(|err| ApiError::MalformedBody(err.to_string()))?;
+            let response = Self::send(|| self.authorize(Request::patch(&request_url)).body(body.clone())).await?;
+            Self::parse_body(&response).await
+        })
+    }
+
+    fn current_user(&self) -> ApiFuture<'_, User> {
+        Box::pin(async move {
+            let response = Self::send(|| self.authorize(Request::get("/user"))).await?;
+            Self::parse_body(&response).await
+        })
+    }
+
+    fn list_teams<'a>(&'a self, organization: &'a str) -> ApiFuture<'a, Vec<Team>> {
+        Box::pin(async move {
+            // Most organizations have fewer than a hundred teams, so this is
+            // usually one request, but keep going until we run out.
+            let mut teams = Vec::new();
+            for page in 1.. {
+                let request_url = format!("/orgs/{organization}/teams?per_page={TEAMS_PER_PAGE}&page={page}");
+                let response = Self::send(|| self.authorize(Request::get(&request_url))).await?;
+                let page_of_teams: Vec<Team> = Self::parse_body(&response).await?;
+                let done = page_of_teams.len() < usize::from(TEAMS_PER_PAGE);
+                teams.extend(page_of_teams);
+                if done {
+                    break
+                }
+            }
+            Ok(teams)
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+
+use crate::services::console;
+use crate::services::github_api::ApiError;
+
+/// GitHub doesn't always send a `Retry-After` with its secondary rate limit
+/// responses, and its docs say to wait at least a minute in that case.
+const SECONDARY_RATE_LIMIT_DELAY_SECS: u64 = 60;
+
+/// Whether a failed request is worth trying again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Something that might well work if we try again later, like a 502,
+    /// a dropped connection, or a secondary rate limit.
+    Transient,
+    /// Something that will fail the same way every time, like a 404 or
+    /// a 422. There's no point retrying these.
+    Permanent,
+}
+
+impl FailureKind {
+    #[must_use]
+    pub const fn of(err: &ApiError) -> Self {
+        match err {
+            ApiError::Network(_)
+            | ApiError::RateLimited { .. }
+            | ApiError::Http { status: 500 | 502 | 503 | 504, .. } => Self::Transient,
+            ApiError::NotFound(_)
+            | ApiError::Unauthorized(_)
+            | ApiError::Forbidden { .. }
+            | ApiError::Http { .. }
+            | ApiError::MalformedBody(_)
+            | ApiError::GraphQl(_) => Self::Permanent,
+        }
+    }
+}
+
+/// Parse a `Retry-After` header, which can either be a number of seconds
+/// or an HTTP date, into a number of seconds from `now`.
+#[must_use]
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds)
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let seconds = (date.with_timezone(&Utc) - now).num_seconds();
+    Some(u64::try_from(seconds).unwrap_or(0))
+}
+
+/// How many times to retry transient failures, and how long to wait between tries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// The delay before the first retry; each later retry waits twice as long
+    /// as the one before.
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base_delay_ms: 1000,
+            max_delay_ms: 60_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retry number `retry` (counting from 0) after
+    /// getting `err`. If GitHub told us how long to wait, we do what it says,
+    /// otherwise we back off exponentially.
+    #[must_use]
+    pub fn delay_ms(&self, retry: u32, err: &ApiError) -> u64 {
+        if let Some(seconds) = err.retry_after() {
+            return seconds.saturating_mul(1000)
+        }
+        if matches!(err, ApiError::RateLimited { .. }) {
+            return SECONDARY_RATE_LIMIT_DELAY_SECS * 1000
+        }
+        self.base_delay_ms
+            .saturating_mul(2_u64.saturating_pow(retry))
+            .min(self.max_delay_ms)
+    }
+
+    /// Call `request` until it succeeds, fails permanently, or we run out of
+    /// retries. Returns the final result along with how many retries it took.
+    #[allow(clippy::future_not_send)]
+    pub async fn run<T, F, Fut>(&self, mut request: F) -> (Result<T, ApiError>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>
+    {
+        let mut retries = 0;
+        loop {
+            let err = match request().await {
+                Ok(value) => return (Ok(value), retries),
+                Err(err) => err
+            };
+            if retries >= self.max_retries || FailureKind::of(&err) == FailureKind::Permanent {
+                return (Err(err), retries)
+            }
+            let delay_ms = self.delay_ms(retries, &err);
+            console::log(&format!("Request failed with <{err}>; retrying in {delay_ms}ms."));
+            sleep(delay_ms).await;
+            retries += 1;
+        }
+    }
+}
+
+/// Wait `delay_ms` milliseconds without blocking the browser.
+#[cfg(target_arch = "wasm32")]
+async fn sleep(delay_ms: u64) {
+    gloo::timers::future::TimeoutFuture::new(u32::try_from(delay_ms).unwrap_or(u32::MAX)).await;
+}
+
+// There are no timers outside the browser (i.e., in the tests), so there we
+// retry straight away.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::unused_async)]
+async fn sleep(_delay_ms: u64) {}
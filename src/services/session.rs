@@ -0,0 +1,124 @@
+use chrono::{DateTime, Datelike, Months, Utc};
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use yewdux::prelude::*;
+
+use crate::page_repo_map::PageRepoMap;
+use crate::repository::{DesiredStateMap, SelectionMode};
+use crate::services::github_api::ListingBackend;
+
+/// The user we're logged in as. We need this to find their saved sessions,
+/// since more than one person might use the same browser.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+pub struct CurrentUser {
+    pub login: Option<String>
+}
+
+/// Everything we need to pick up where the user left off with an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub organization: String,
+    pub backend: ListingBackend,
+    pub mode: SelectionMode,
+    pub desired_state_map: DesiredStateMap,
+    pub page_repo_map: PageRepoMap,
+    pub saved_at: DateTime<Utc>
+}
+
+impl SavedSession {
+    /// How many repositories are currently selected to be archived or unarchived.
+    #[must_use]
+    pub fn selected_count(&self) -> usize {
+        self.desired_state_map.map.values().filter(|(_, state)| state.is_selected()).count()
+    }
+}
+
+/// Where we are with saving and restoring sessions.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Store)]
+pub enum SessionStatus {
+    /// We don't know who's logged in yet, so we can't look anything up.
+    #[default]
+    Loading,
+    /// There's a saved session the user hasn't yet chosen to resume or throw
+    /// away. We don't save anything until they decide, so we don't overwrite it.
+    Offered(Box<SavedSession>),
+    /// Changes are being saved as they're made.
+    Active
+}
+
+fn session_key(login: &str, organization: &str) -> String {
+    format!("ice-repos:session:{login}:{organization}")
+}
+
+fn last_organization_key(login: &str) -> String {
+    format!("ice-repos:last-organization:{login}")
+}
+
+/// The saved session for `organization`, if there is one.
+#[must_use]
+pub fn load(login: &str, organization: &str) -> Option<SavedSession> {
+    LocalStorage::get(session_key(login, organization)).ok()
+}
+
+/// The session `login` was most recently working on, if there is one.
+#[must_use]
+pub fn most_recent(login: &str) -> Option<SavedSession> {
+    let organization: String = LocalStorage::get(last_organization_key(login)).ok()?;
+    load(login, &organization)
+}
+
+/// Save `session`, replacing any earlier one for the same organization.
+pub fn save(login: &str, session: &SavedSession) {
+    let result = LocalStorage::set(session_key(login, &session.organization), session)
+        .and_then(|()| LocalStorage::set(last_organization_key(login), &session.organization));
+    if let Err(err) = result {
+        web_sys::console::warn_1(&format!("Unable to save the session for {}: {err}", session.organization).into());
+    }
+}
+
+/// Throw away the saved session for `organization`.
+pub fn discard(login: &str, organization: &str) {
+    LocalStorage::delete(session_key(login, organization));
+    let last_organization: Option<String> = LocalStorage::get(last_organization_key(login)).ok();
+    if last_organization.as_deref() == Some(organization) {
+        LocalStorage::delete(last_organization_key(login));
+    }
+}
+
+/// A rough, human-friendly description of how long ago `then` was.
+#[must_use]
+pub fn describe_age(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now - then;
+    let plural = |n: i64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+    let months = whole_months(then, now);
+    if age.num_minutes() < 1 {
+        "just now".to_owned()
+    } else if age.num_hours() < 1 {
+        plural(age.num_minutes(), "minute")
+    } else if age.num_days() < 1 {
+        plural(age.num_hours(), "hour")
+    } else if months < 1 {
+        plural(age.num_days(), "day")
+    } else if months < 12 {
+        plural(months, "month")
+    } else {
+        plural(months / 12, "year")
+    }
+}
+
+// How many calendar months have gone by between `then` and `now`, so that
+// e.g. August 1st to September 1st is a month however many days that is.
+fn whole_months(then: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    let months = i64::from(now.year() - then.year()) * 12 + i64::from(now.month())
+        - i64::from(then.month());
+    let reached = u32::try_from(months)
+        .ok()
+        .and_then(|months| then.checked_add_months(Months::new(months)))
+        .is_some_and(|later| later <= now);
+    if reached {
+        months
+    } else {
+        months - 1
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::repo;
+use ice_repos::services::archive_repos::{outcome_of, ArchiveOutcome};
+use ice_repos::services::github_api::ApiError;
+
+fn forbidden(message: &str) -> ApiError {
+    ApiError::Forbidden { message: message.to_owned(), sso_url: None }
+}
+
+#[test]
+fn a_403_about_archiving_means_someone_beat_us_to_it() {
+    let result = Err(forbidden("Repository was archived so is read-only."));
+    assert_eq!(outcome_of(result, true), ArchiveOutcome::AlreadyArchived);
+}
+
+#[test]
+fn other_403s_are_permission_problems() {
+    let result = Err(forbidden("Must have admin rights to Repository."));
+    assert_eq!(outcome_of(result, true), ArchiveOutcome::PermissionDenied);
+}
+
+#[test]
+fn a_403_about_archiving_while_unarchiving_is_a_permission_problem() {
+    let result = Err(forbidden("Repository was archived so is read-only."));
+    assert_eq!(outcome_of(result, false), ArchiveOutcome::PermissionDenied);
+}
+
+#[test]
+fn successes_depend_on_which_way_we_went() {
+    assert_eq!(outcome_of(Ok(repo(1).build()), true), ArchiveOutcome::Archived);
+    assert_eq!(outcome_of(Ok(repo(1).build()), false), ArchiveOutcome::Unarchived);
+}
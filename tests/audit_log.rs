@@ -0,0 +1,89 @@
+use chrono::{TimeZone, Utc};
+
+use ice_repos::repository::{DesiredState, RepoKey, SelectionMode};
+use ice_repos::services::archive_repos::{ArchiveOutcome, ArchiveResult};
+use ice_repos::services::audit_log::{AuditBatch, AuditEntry, AuditLog};
+use ice_repos::services::github_api::{ApiError, RepoUpdate};
+
+fn result(id: usize, outcome: ArchiveOutcome, sent_request: bool, status: Option<u16>) -> ArchiveResult {
+    ArchiveResult { key: RepoKey::new("example", id), owner: "example".to_owned(), name: format!("repo-{id}"), outcome, retries: 0, sent_request, status }
+}
+
+fn log() -> AuditLog {
+    let at = Utc.with_ymd_and_hms(2022, 8, 1, 12, 0, 0).unwrap();
+    let mut batch = AuditBatch::new(at, Some("instructor".to_owned()), vec!["example".to_owned()], SelectionMode::Archive,
+        vec!["example/repo-1".to_owned(), "example/repo-2".to_owned(), "example/repo-3".to_owned()]);
+    batch.entries.push(AuditEntry::from_result(SelectionMode::Archive, &result(1, ArchiveOutcome::Archived, true, Some(200)), at));
+    batch.entries.push(AuditEntry::from_result(SelectionMode::Archive,
+        &result(2, ArchiveOutcome::Error("Validation | failed".to_owned()), true, Some(422)), at));
+    batch.entries.push(AuditEntry::cancelled(&RepoKey::new("example", 3), "repo-3".to_owned(), DesiredState::Archive, at));
+    batch.finished_at = Some(at);
+    AuditLog { batches: vec![batch] }
+}
+
+#[test]
+fn entries_record_the_request_we_sent() {
+    let entry = AuditEntry::from_result(SelectionMode::Unarchive,
+        &result(7, ArchiveOutcome::Unarchived, true, Some(200)), Utc::now());
+
+    let request = entry.request.unwrap();
+    assert_eq!(request.method, "PATCH");
+    assert_eq!(request.path, "/repos/example/repo-7");
+    assert_eq!(request.body, RepoUpdate { archived: Some(false) });
+    assert_eq!(entry.status, Some(200));
+    assert_eq!(entry.final_state, DesiredState::Unarchived);
+}
+
+#[test]
+fn entries_without_a_request_say_so() {
+    let entry = AuditEntry::from_result(SelectionMode::Archive,
+        &result(7, ArchiveOutcome::AlreadyArchived, false, None), Utc::now());
+    assert_eq!(entry.request, None);
+    assert_eq!(entry.status, None);
+    assert_eq!(entry.final_state, DesiredState::AlreadyArchived);
+
+    let entry = AuditEntry::cancelled(&RepoKey::new("example", 8), "repo-8".to_owned(), DesiredState::Archive, Utc::now());
+    assert_eq!(entry.request, None);
+    assert_eq!(entry.outcome, "Cancelled");
+    assert_eq!(entry.final_state, DesiredState::Archive);
+}
+
+#[test]
+fn failures_keep_their_http_status() {
+    let entry = AuditEntry::from_result(SelectionMode::Archive,
+        &result(7, ArchiveOutcome::PermissionDenied, true, ApiError::Forbidden { message: String::new(), sso_url: None }.status()), Utc::now());
+    assert_eq!(entry.status, Some(403));
+    assert_eq!(entry.final_state, DesiredState::Failed);
+    assert_eq!(entry.outcome, "Permission denied");
+
+    assert_eq!(ApiError::from_response(429, String::new(), Some(5), None).status(), Some(429));
+    assert_eq!(ApiError::Network(String::new()).status(), None);
+}
+
+#[test]
+fn the_log_round_trips_through_json() {
+    let log = log();
+    let restored: AuditLog = serde_json::from_str(&log.to_json()).unwrap();
+    assert_eq!(restored, log);
+}
+
+#[test]
+fn the_markdown_log_has_a_row_per_repository() {
+    let markdown = log().to_markdown();
+
+    assert!(markdown.contains("## Archive batch for example, started 2022-08-01T12:00:00+00:00"));
+    assert!(markdown.contains("- Submitted by: instructor"));
+    assert!(markdown.contains("- Repositories submitted: 3"));
+    assert!(markdown.contains(r#"| example | repo-1 | `PATCH /repos/example/repo-1 {"archived":true}` | 200 | Archived | 0 | Archived |"#));
+    // Pipes in messages would otherwise split the cell.
+    assert!(markdown.contains(r"| 422 | Error: Validation \| failed | 0 | Failed |"));
+    assert!(markdown.contains("| example | repo-3 | none | - | Cancelled | 0 | Archive |"));
+}
+
+#[test]
+fn interrupted_batches_are_marked() {
+    let mut log = log();
+    log.batches[0].finished_at = None;
+    assert!(log.to_markdown().contains("- Finished: no (the batch was interrupted)"));
+    assert!(AuditLog::default().to_markdown().contains("No batches have been submitted."));
+}
@@ -0,0 +1,118 @@
+use ice_repos::repository::RepoKey;
+use ice_repos::services::archive_repos::{ArchiveOutcome, ArchiveResult};
+use ice_repos::services::batch::{BatchEvent, BatchProgress, RepoProgress};
+
+fn key(id: usize) -> RepoKey {
+    RepoKey::new("example", id)
+}
+
+fn result(id: usize, outcome: ArchiveOutcome) -> ArchiveResult {
+    ArchiveResult {
+        key: key(id),
+        owner: "example".to_owned(),
+        name: format!("repo-{id}"),
+        outcome,
+        retries: 0,
+        sent_request: true,
+        status: Some(200)
+    }
+}
+
+fn started(ids: &[usize]) -> BatchProgress {
+    let mut progress = BatchProgress::default();
+    progress.apply(BatchEvent::Started);
+    for id in ids {
+        progress.apply(BatchEvent::Queued { key: key(*id), name: format!("repo-{id}") });
+    }
+    progress
+}
+
+fn progress_of(progress: &BatchProgress) -> Vec<RepoProgress> {
+    progress.entries.iter().map(|entry| entry.progress.clone()).collect()
+}
+
+#[test]
+fn queued_repositories_are_not_completed() {
+    let progress = started(&[1, 2, 3]);
+    assert!(progress.running);
+    assert_eq!((progress.total(), progress.completed()), (3, 0));
+    assert_eq!(progress_of(&progress), vec![RepoProgress::Queued; 3]);
+}
+
+#[test]
+fn successes_failures_and_cancellations_all_complete() {
+    let mut progress = started(&[1, 2, 3, 4]);
+    progress
+        .apply(BatchEvent::InFlight(key(1)))
+        .apply(BatchEvent::InFlight(key(2)))
+        .apply(BatchEvent::Done(result(1, ArchiveOutcome::Archived)))
+        .apply(BatchEvent::Failed(result(2, ArchiveOutcome::PermissionDenied)))
+        .apply(BatchEvent::InFlight(key(3)));
+    assert_eq!((progress.total(), progress.completed()), (4, 2));
+
+    progress
+        .apply(BatchEvent::Cancelled(key(4)))
+        .apply(BatchEvent::Done(result(3, ArchiveOutcome::AlreadyArchived)));
+    assert_eq!((progress.total(), progress.completed()), (4, 4));
+    assert_eq!(progress.results(), [
+        result(1, ArchiveOutcome::Archived),
+        result(2, ArchiveOutcome::PermissionDenied),
+        result(3, ArchiveOutcome::AlreadyArchived)
+    ]);
+    assert_eq!(progress_of(&progress)[3], RepoProgress::Cancelled);
+    assert!(progress.running);
+}
+
+#[test]
+fn finishing_stops_the_batch() {
+    let mut progress = started(&[1, 2]);
+    progress
+        .apply(BatchEvent::Done(result(1, ArchiveOutcome::Archived)))
+        .apply(BatchEvent::Cancelled(key(2)))
+        .apply(BatchEvent::Finished);
+    assert!(!progress.running);
+    assert_eq!((progress.total(), progress.completed()), (2, 2));
+}
+
+#[test]
+fn an_empty_batch_finishes_straight_away() {
+    let mut progress = started(&[]);
+    progress.apply(BatchEvent::Finished);
+    assert!(!progress.running);
+    assert_eq!((progress.total(), progress.completed()), (0, 0));
+}
+
+#[test]
+fn starting_again_forgets_the_last_batch() {
+    let mut progress = started(&[1, 2]);
+    progress
+        .apply(BatchEvent::Done(result(1, ArchiveOutcome::Archived)))
+        .apply(BatchEvent::Finished)
+        .apply(BatchEvent::Started)
+        .apply(BatchEvent::Queued { key: key(3), name: "repo-3".to_owned() });
+    assert!(progress.running);
+    assert_eq!((progress.total(), progress.completed()), (1, 0));
+    assert!(progress.results().is_empty());
+}
+
+#[test]
+fn events_for_repositories_not_in_the_batch_are_ignored() {
+    let mut progress = started(&[1]);
+    progress
+        .apply(BatchEvent::Done(result(2, ArchiveOutcome::Archived)))
+        .apply(BatchEvent::Cancelled(key(3)));
+    assert_eq!((progress.total(), progress.completed()), (1, 0));
+}
+
+#[test]
+fn results_we_could_not_record_are_kept_with_their_entry() {
+    let mut progress = started(&[1, 2]);
+    progress
+        .apply(BatchEvent::Done(result(1, ArchiveOutcome::Archived)))
+        .apply(BatchEvent::NotRecorded { key: key(1), message: "kept in review".to_owned() })
+        .apply(BatchEvent::NotRecorded { key: key(3), message: "not in the batch".to_owned() });
+    let not_recorded: Vec<Option<&str>> = progress.entries.iter().map(|entry| entry.not_recorded.as_deref()).collect();
+    assert_eq!(not_recorded, [Some("kept in review"), None]);
+    assert_eq!(progress.results(), [result(1, ArchiveOutcome::Archived)]);
+    assert_eq!(progress.completed(), 1);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use ice_repos::classroom::group_by_assignment;
+use ice_repos::repository::{RepoKey, Repository};
+
+use common::repo;
+
+fn repos(names: &[(&str, &str)]) -> Vec<(RepoKey, Repository)> {
+    names.iter().enumerate().map(|(i, (name, pushed_at))| {
+        let id = i + 1;
+        (RepoKey::new("classroom", id), repo(id).with_name(name).with_pushed_at(pushed_at).with_private(true).build())
+    }).collect()
+}
+
+fn names(repos: &[(RepoKey, Repository)], keys: &[RepoKey]) -> Vec<String> {
+    keys.iter().map(|key| repos.iter().find(|(k, _)| k == key).unwrap().1.name.clone()).collect()
+}
+
+const PUSHED: &str = "2024-01-01T12:00:00Z";
+
+#[test]
+fn repositories_are_grouped_by_assignment() {
+    let repos = repos(&[
+        ("website", PUSHED),
+        ("lab-01-alice", "2024-02-01T12:00:00Z"),
+        ("lab-01-bob", "2024-03-01T12:00:00Z"),
+        ("lab-02-alice", PUSHED),
+        ("lab-02-bob", PUSHED),
+        ("lab-02-carol", PUSHED),
+    ]);
+    let grouping = group_by_assignment(repos.iter().map(|(key, repo)| (key, repo)));
+
+    let prefixes: Vec<&str> = grouping.groups.iter().map(|group| group.prefix.as_str()).collect();
+    assert_eq!(prefixes, vec!["lab-01", "lab-02"]);
+    assert_eq!(names(&repos, &grouping.groups[0].keys), vec!["lab-01-alice", "lab-01-bob"]);
+    assert_eq!(grouping.groups[1].keys.len(), 3);
+    assert_eq!(grouping.groups[0].latest_push, repos[2].1.pushed_at);
+    assert_eq!(names(&repos, &grouping.ungrouped), vec!["website"]);
+}
+
+#[test]
+fn usernames_can_contain_dashes() {
+    let repos = repos(&[
+        ("hw1-mary-jane", PUSHED),
+        ("hw1-mary-ann", PUSHED),
+        ("hw1-bob", PUSHED),
+        ("hw1-carol", PUSHED),
+        ("hw1-dave", PUSHED),
+    ]);
+    let grouping = group_by_assignment(repos.iter().map(|(key, repo)| (key, repo)));
+    assert_eq!(grouping.groups.len(), 1);
+    assert_eq!(grouping.groups[0].prefix, "hw1");
+    assert_eq!(grouping.groups[0].keys.len(), 5);
+    assert!(grouping.ungrouped.is_empty());
+}
+
+#[test]
+fn starter_repositories_and_capitals_go_with_their_assignment() {
+    let repos = repos(&[
+        ("CS101-Lab-1", PUSHED),
+        ("cs101-lab-1-alice", PUSHED),
+        ("CS101-Lab-1-Bob", PUSHED),
+        ("cs101-lab-2-alice", PUSHED),
+        ("cs101-lab-2-bob", PUSHED),
+    ]);
+    let grouping = group_by_assignment(repos.iter().map(|(key, repo)| (key, repo)));
+    let prefixes: Vec<&str> = grouping.groups.iter().map(|group| group.prefix.as_str()).collect();
+    assert_eq!(prefixes, vec!["CS101-Lab-1", "cs101-lab-2"]);
+    assert_eq!(grouping.groups[0].keys.len(), 3);
+}
+
+#[test]
+fn lone_repositories_are_not_assignments() {
+    let repos = repos(&[("hw1-alice", PUSHED), ("hw2-alice", PUSHED), ("notes", PUSHED)]);
+    let grouping = group_by_assignment(repos.iter().map(|(key, repo)| (key, repo)));
+    assert!(grouping.groups.is_empty());
+    assert_eq!(names(&repos, &grouping.ungrouped), vec!["hw1-alice", "hw2-alice", "notes"]);
+}
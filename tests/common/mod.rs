@@ -0,0 +1,131 @@
+//! Helpers shared by the integration tests.
+
+// Each test file only uses some of these.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use ice_repos::repository::{RepoId, RepoPermissions, Repository, Visibility};
+
+use serde_json::json;
+
+/// When repositories were pushed to and updated, unless a test says otherwise.
+pub const PUSHED_AT: &str = "2022-08-01T12:00:00Z";
+
+/// Builds a `Repository` the way GitHub would describe it, starting from an
+/// unarchived public repository named `repo-{id}` in `example`.
+#[derive(Debug, Clone)]
+pub struct RepoBuilder {
+    repo: Repository
+}
+
+#[must_use]
+pub fn repo(id: RepoId) -> RepoBuilder {
+    RepoBuilder {
+        repo: serde_json::from_value(json!({
+            "id": id,
+            "name": format!("repo-{id}"),
+            "description": null,
+            "archived": false,
+            "updated_at": PUSHED_AT,
+            "pushed_at": PUSHED_AT,
+            "fork": false,
+            "private": false,
+            "language": null,
+            "size": 0,
+            "default_branch": "main",
+            "open_issues_count": 0,
+            "html_url": format!("https://github.com/example/repo-{id}")
+        })).unwrap()
+    }
+}
+
+impl RepoBuilder {
+    #[must_use]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.repo.name = name.to_owned();
+        self.repo.html_url = format!("https://github.com/example/{name}");
+        self
+    }
+
+    #[must_use]
+    pub fn with_full_name(mut self, full_name: &str) -> Self {
+        self.repo.full_name = Some(full_name.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.repo.description = Some(description.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn with_archived(mut self, archived: bool) -> Self {
+        self.repo.archived = archived;
+        self
+    }
+
+    /// Also sets `updated_at`, since pushing updates a repository.
+    #[must_use]
+    pub fn with_pushed_at(mut self, pushed_at: &str) -> Self {
+        self.repo.pushed_at = pushed_at.parse().unwrap();
+        self.repo.updated_at = self.repo.pushed_at;
+        self
+    }
+
+    #[must_use]
+    pub fn with_updated_at(mut self, updated_at: &str) -> Self {
+        self.repo.updated_at = updated_at.parse().unwrap();
+        self
+    }
+
+    #[must_use]
+    pub const fn with_fork(mut self, fork: bool) -> Self {
+        self.repo.fork = fork;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_private(mut self, private: bool) -> Self {
+        self.repo.private = private;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.repo.visibility = Some(visibility);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_template(mut self, is_template: bool) -> Self {
+        self.repo.is_template = is_template;
+        self
+    }
+
+    #[must_use]
+    pub fn with_permissions(mut self, permissions: RepoPermissions) -> Self {
+        self.repo.permissions = Some(permissions);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Repository {
+        self.repo
+    }
+}
+
+/// Run `future` to completion. Everything the tests await (e.g., `FakeGitHubApi`)
+/// is ready straight away, so there's nothing to wake us up and we just poll.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output
+        }
+    }
+}
@@ -0,0 +1,357 @@
+mod common;
+
+use ice_repos::repository::{
+    DesiredState, DesiredStateMap, Organizations, Phase, RepoId, RepoKey, Repository, SelectionMode, TransitionError, TRANSITIONS
+};
+
+use common::repo;
+
+const ALL_STATES: [DesiredState; 8] = [
+    DesiredState::AlreadyArchived,
+    DesiredState::Keep,
+    DesiredState::Archive,
+    DesiredState::KeptInReview,
+    DesiredState::Unarchive,
+    DesiredState::Archived,
+    DesiredState::Unarchived,
+    DesiredState::Failed,
+];
+
+fn key(id: RepoId) -> RepoKey {
+    RepoKey::new("example", id)
+}
+
+fn ids_of(keys: Vec<RepoKey>) -> Vec<RepoId> {
+    keys.into_iter().map(|key| key.id).collect()
+}
+
+/// A map with one repository in each state. Repository `i` is in `ALL_STATES[i]`,
+/// with an `archived` flag that fits that state.
+fn map_with_every_state() -> DesiredStateMap {
+    let mut map = DesiredStateMap::default();
+    for (id, state) in ALL_STATES.into_iter().enumerate() {
+        let archived = state.required_archived_flag().unwrap_or(id % 2 == 0);
+        map.map.insert(key(id), (repo(id).with_archived(archived).build(), state));
+    }
+    map
+}
+
+fn state_of(map: &DesiredStateMap, id: RepoId) -> DesiredState {
+    map.map[&key(id)].1
+}
+
+fn sorted(mut ids: Vec<RepoId>) -> Vec<RepoId> {
+    ids.sort_unstable();
+    ids
+}
+
+fn id_of(state: DesiredState) -> RepoId {
+    ALL_STATES.iter().position(|s| *s == state).unwrap()
+}
+
+#[test]
+fn new_repos_start_out_selected_unless_already_archived() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).with_archived(true).build()]);
+    assert_eq!(state_of(&map, 1), DesiredState::Archive);
+    assert_eq!(state_of(&map, 2), DesiredState::AlreadyArchived);
+    assert_eq!(map.get_desired_state(&key(1)), Some(true));
+    assert_eq!(map.get_desired_state(&key(2)), Some(false));
+    assert_eq!(map.get_desired_state(&key(3)), None);
+}
+
+#[test]
+fn with_repos_leaves_existing_choices_alone() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    map.transition(&key(1), DesiredState::Keep, Phase::Paginator).unwrap();
+    map.with_repos("example", &[repo(1).build()]);
+    assert_eq!(state_of(&map, 1), DesiredState::Keep);
+}
+
+#[test]
+fn paginator_checkboxes_toggle_between_states() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).with_archived(true).build()]);
+    for (id, mode, checked, unchecked) in [
+        (1, SelectionMode::Archive, DesiredState::Archive, DesiredState::Keep),
+        (2, SelectionMode::Unarchive, DesiredState::Unarchive, DesiredState::AlreadyArchived),
+    ] {
+        map.transition(&key(id), DesiredState::from_paginator_state(false, mode), Phase::Paginator).unwrap();
+        assert_eq!(state_of(&map, id), unchecked);
+        map.transition(&key(id), DesiredState::from_paginator_state(true, mode), Phase::Paginator).unwrap();
+        assert_eq!(state_of(&map, id), checked);
+    }
+}
+
+#[test]
+fn review_checkboxes_toggle_between_states() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    map.transition(&key(1), DesiredState::from_review_state(false, SelectionMode::Archive), Phase::Review).unwrap();
+    assert_eq!(state_of(&map, 1), DesiredState::KeptInReview);
+    assert_eq!(ids_of(map.get_repo_keys_to_review(SelectionMode::Archive)), vec![1]);
+    map.transition(&key(1), DesiredState::from_review_state(true, SelectionMode::Archive), Phase::Review).unwrap();
+    assert_eq!(state_of(&map, 1), DesiredState::Archive);
+}
+
+#[test]
+fn staying_put_is_always_allowed() {
+    for phase in [Phase::Paginator, Phase::Review, Phase::Submission] {
+        for state in ALL_STATES {
+            assert!(state.can_transition_to(state, phase), "{state:?} in {phase:?}");
+        }
+    }
+}
+
+#[test]
+fn only_transitions_in_the_table_are_allowed() {
+    for phase in [Phase::Paginator, Phase::Review, Phase::Submission] {
+        for from in ALL_STATES {
+            for to in ALL_STATES {
+                let in_table = TRANSITIONS.contains(&(phase, from, to));
+                assert_eq!(from.can_transition_to(to, phase), from == to || in_table,
+                    "{from:?} -> {to:?} in {phase:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn illegal_transitions_are_rejected_and_change_nothing() {
+    let mut map = map_with_every_state();
+    let before = map.clone();
+    let cases = [
+        // The review can't touch repositories we chose to keep in the paginator.
+        (id_of(DesiredState::Keep), DesiredState::Archive, Phase::Review),
+        (id_of(DesiredState::AlreadyArchived), DesiredState::Unarchive, Phase::Review),
+        // Only GitHub can tell us something has been archived.
+        (id_of(DesiredState::Archive), DesiredState::Archived, Phase::Paginator),
+        (id_of(DesiredState::Archive), DesiredState::Archived, Phase::Review),
+        // We only get results back for repositories we submitted.
+        (id_of(DesiredState::Keep), DesiredState::Archived, Phase::Submission),
+        (id_of(DesiredState::KeptInReview), DesiredState::Failed, Phase::Submission),
+    ];
+    for (id, to, phase) in cases {
+        let from = state_of(&map, id);
+        assert_eq!(map.transition(&key(id), to, phase).map(|_| ()), Err(TransitionError::Illegal { key: key(id), from, to, phase }));
+    }
+    assert_eq!(map, before);
+}
+
+#[test]
+fn transitions_must_fit_the_archived_flag() {
+    let mut map = DesiredStateMap::default();
+    map.map.insert(key(1), (repo(1).with_archived(true).build(), DesiredState::KeptInReview));
+    assert_eq!(
+        map.transition(&key(1), DesiredState::Archive, Phase::Review).map(|_| ()),
+        Err(TransitionError::WrongArchivedFlag { key: key(1), to: DesiredState::Archive, archived: true })
+    );
+    map.transition(&key(1), DesiredState::Unarchive, Phase::Review).unwrap();
+    assert_eq!(state_of(&map, 1), DesiredState::Unarchive);
+}
+
+#[test]
+fn unknown_repos_are_rejected() {
+    let mut map = DesiredStateMap::default();
+    assert_eq!(
+        map.transition(&key(7), DesiredState::Keep, Phase::Paginator).map(|_| ()),
+        Err(TransitionError::UnknownRepo(key(7)))
+    );
+}
+
+#[test]
+fn submission_results_update_the_archived_flag() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).with_archived(true).build()]);
+    map.transition(&key(2), DesiredState::Unarchive, Phase::Paginator).unwrap();
+
+    map.transition(&key(1), DesiredState::Archived, Phase::Submission).unwrap();
+    map.transition(&key(2), DesiredState::Unarchived, Phase::Submission).unwrap();
+    assert!(map.get_repo(&key(1)).archived);
+    assert!(!map.get_repo(&key(2)).archived);
+    assert!(map.get_owned_repos_to_change(SelectionMode::Archive).is_empty());
+    assert!(map.get_owned_repos_to_change(SelectionMode::Unarchive).is_empty());
+}
+
+#[test]
+fn repos_changed_this_session_can_be_kept_as_they_are() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).with_archived(true).build()]);
+    map.transition(&key(1), DesiredState::Archived, Phase::Submission).unwrap();
+    map.transition(&key(2), DesiredState::Unarchive, Phase::Paginator).unwrap();
+    map.transition(&key(2), DesiredState::Unarchived, Phase::Submission).unwrap();
+
+    map.transition(&key(2), DesiredState::Keep, Phase::Paginator).unwrap();
+    map.transition(&key(1), DesiredState::AlreadyArchived, Phase::Paginator).unwrap();
+    assert_eq!(map.get_desired_state(&key(2)), Some(false));
+    assert_eq!(map.get_desired_state(&key(1)), Some(false));
+    assert!(map.get_owned_repos_to_change(SelectionMode::Archive).is_empty());
+    assert!(map.get_owned_repos_to_change(SelectionMode::Unarchive).is_empty());
+}
+
+#[test]
+fn failed_repos_go_back_into_the_review() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    map.transition(&key(1), DesiredState::Failed, Phase::Submission).unwrap();
+    assert_eq!(ids_of(map.get_repo_keys_to_review(SelectionMode::Archive)), vec![1]);
+    assert_eq!(map.get_desired_state(&key(1)), Some(false));
+    assert!(map.get_owned_repos_to_change(SelectionMode::Archive).is_empty());
+    map.transition(&key(1), DesiredState::Archive, Phase::Review).unwrap();
+    assert_eq!(map.get_owned_repos_to_change(SelectionMode::Archive)[0].1.id, 1);
+}
+
+#[test]
+fn kept_repos_are_not_reviewed() {
+    // This used to be `!= AlreadyArchived || != Keep`, which is always true.
+    let map = map_with_every_state();
+    let to_review = ids_of(map.get_repo_keys_to_review(SelectionMode::Archive));
+    assert!(!to_review.contains(&id_of(DesiredState::Keep)));
+    assert!(!to_review.contains(&id_of(DesiredState::AlreadyArchived)));
+}
+
+#[test]
+fn review_queries_come_from_the_table() {
+    let map = map_with_every_state();
+    // `KeptInReview` and `Failed` don't require a particular archived flag, and
+    // have odd ids, so `map_with_every_state` leaves them unarchived.
+    assert_eq!(sorted(ids_of(map.get_repo_keys_to_review(SelectionMode::Archive))),
+        vec![id_of(DesiredState::Archive), id_of(DesiredState::KeptInReview), id_of(DesiredState::Failed)]);
+    assert_eq!(sorted(ids_of(map.get_repo_keys_to_review(SelectionMode::Unarchive))),
+        vec![id_of(DesiredState::Unarchive)]);
+    for (id, state) in ALL_STATES.into_iter().enumerate() {
+        let reviewed = ids_of(map.get_repo_keys_to_review(SelectionMode::Archive)).contains(&id)
+            || ids_of(map.get_repo_keys_to_review(SelectionMode::Unarchive)).contains(&id);
+        if reviewed {
+            assert!(state.has_transitions_in(Phase::Review), "{state:?}");
+        }
+    }
+}
+
+#[test]
+fn submit_queries_come_from_the_table() {
+    let map = map_with_every_state();
+    let ids = |repos: Vec<(RepoKey, Repository)>| repos.into_iter().map(|(key, _)| key.id).collect::<Vec<_>>();
+    assert_eq!(ids(map.get_owned_repos_to_change(SelectionMode::Archive)), vec![id_of(DesiredState::Archive)]);
+    assert_eq!(ids(map.get_owned_repos_to_change(SelectionMode::Unarchive)), vec![id_of(DesiredState::Unarchive)]);
+}
+
+#[test]
+fn undo_and_redo_single_changes() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    assert!(!map.can_undo());
+    map.transition(&key(1), DesiredState::Keep, Phase::Paginator).unwrap();
+    map.transition(&key(1), DesiredState::Archive, Phase::Paginator).unwrap();
+
+    assert!(map.undo());
+    assert_eq!(state_of(&map, 1), DesiredState::Keep);
+    assert!(map.undo());
+    assert_eq!(state_of(&map, 1), DesiredState::Archive);
+    assert!(!map.undo());
+
+    assert!(map.redo());
+    assert_eq!(state_of(&map, 1), DesiredState::Keep);
+    assert!(map.can_redo());
+}
+
+#[test]
+fn bulk_changes_undo_together() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).build(), repo(3).with_archived(true).build()]);
+    let errors = map.transition_all([1, 2, 3].map(|id| (key(id), DesiredState::Keep)), Phase::Paginator);
+    // Repository 3 is archived, so it can't be `Keep`.
+    assert_eq!(errors, vec![TransitionError::Illegal {
+        key: key(3), from: DesiredState::AlreadyArchived, to: DesiredState::Keep, phase: Phase::Paginator
+    }]);
+    assert_eq!([1, 2, 3].map(|id| state_of(&map, id)), [DesiredState::Keep, DesiredState::Keep, DesiredState::AlreadyArchived]);
+
+    assert!(map.undo());
+    assert_eq!([1, 2, 3].map(|id| state_of(&map, id)), [DesiredState::Archive, DesiredState::Archive, DesiredState::AlreadyArchived]);
+    assert!(!map.can_undo());
+    assert!(map.redo());
+    assert_eq!([1, 2].map(|id| state_of(&map, id)), [DesiredState::Keep, DesiredState::Keep]);
+}
+
+#[test]
+fn new_changes_clear_the_redo_history() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).build()]);
+    map.transition(&key(1), DesiredState::Keep, Phase::Paginator).unwrap();
+    map.undo();
+    map.transition(&key(2), DesiredState::Keep, Phase::Paginator).unwrap();
+    assert!(!map.can_redo());
+}
+
+#[test]
+fn submission_results_are_not_undoable() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    map.transition(&key(1), DesiredState::KeptInReview, Phase::Review).unwrap();
+    map.transition(&key(1), DesiredState::Archive, Phase::Review).unwrap();
+    map.transition(&key(1), DesiredState::Archived, Phase::Submission).unwrap();
+    // Undoing the review change would un-select a repository GitHub has
+    // already archived, so it's skipped.
+    assert!(map.undo());
+    assert_eq!(state_of(&map, 1), DesiredState::Archived);
+}
+
+#[test]
+fn repo_keys_round_trip_as_strings() {
+    let key = RepoKey::new("example", 42);
+    assert_eq!(key.to_string(), "example/42");
+    assert_eq!("example/42".parse::<RepoKey>().unwrap(), key);
+    assert!("example".parse::<RepoKey>().is_err());
+    assert!("example/x".parse::<RepoKey>().is_err());
+
+    let map = map_with_every_state();
+    let restored: DesiredStateMap = serde_json::from_str(&serde_json::to_string(&map).unwrap()).unwrap();
+    assert_eq!(restored.map, map.map);
+}
+
+#[test]
+fn repositories_with_the_same_id_in_different_organizations_are_kept_apart() {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    map.with_repos("elsewhere", &[repo(1).build()]);
+    map.transition(&RepoKey::new("elsewhere", 1), DesiredState::Keep, Phase::Paginator).unwrap();
+
+    assert_eq!(map.organizations(), vec!["elsewhere", "example"]);
+    assert_eq!(state_of(&map, 1), DesiredState::Archive);
+    assert_eq!(map.for_organization("elsewhere").map.len(), 1);
+
+    map.remove_organization("elsewhere");
+    assert_eq!(map.organizations(), vec!["example"]);
+    // The undo history for the removed organization goes with it.
+    assert!(!map.can_undo());
+}
+
+#[test]
+fn merging_replaces_only_the_merged_repositories() {
+    let mut saved = DesiredStateMap::default();
+    saved.with_repos("elsewhere", &[repo(1).build()]);
+    saved.transition(&RepoKey::new("elsewhere", 1), DesiredState::Keep, Phase::Paginator).unwrap();
+
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build()]);
+    map.with_repos("elsewhere", &[repo(1).build()]);
+    map.merge(saved.for_organization("elsewhere"));
+
+    assert_eq!(state_of(&map, 1), DesiredState::Archive);
+    assert_eq!(map.map[&RepoKey::new("elsewhere", 1)].1, DesiredState::Keep);
+}
+
+#[test]
+fn organizations_are_added_once_and_removed_cleanly() {
+    let mut organizations = Organizations::default();
+    organizations.add("example").add("elsewhere").add("Example");
+    assert_eq!(organizations.names, vec!["example", "elsewhere"]);
+    assert_eq!(organizations.current.as_deref(), Some("example"));
+
+    organizations.remove("example");
+    assert_eq!(organizations.current.as_deref(), Some("elsewhere"));
+    organizations.remove("elsewhere");
+    assert_eq!(organizations.current, None);
+    assert!(!organizations.contains("elsewhere"));
+}
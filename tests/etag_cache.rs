@@ -0,0 +1,54 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ice_repos::services::etag_cache::{CacheEntrySummary, EtagCacheSummary};
+
+fn stored_at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2022, 8, 1, 12, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+fn entry(url: &str, bytes: usize, minutes: i64) -> CacheEntrySummary {
+    CacheEntrySummary { url: url.to_owned(), bytes, stored_at: stored_at(minutes) }
+}
+
+fn urls(summary: &EtagCacheSummary) -> Vec<&str> {
+    summary.entries.iter().map(|entry| entry.url.as_str()).collect()
+}
+
+#[test]
+fn storing_a_url_again_replaces_it() {
+    let mut summary = EtagCacheSummary::default();
+    summary.record(entry("/page/1", 100, 0)).record(entry("/page/2", 200, 1)).record(entry("/page/1", 50, 2));
+    assert_eq!(urls(&summary), ["/page/2", "/page/1"]);
+    assert_eq!(summary.total_bytes(), 250);
+
+    summary.forget("/page/2").forget("/page/3");
+    assert_eq!(urls(&summary), ["/page/1"]);
+}
+
+#[test]
+fn nothing_is_evicted_under_the_limit() {
+    let mut summary = EtagCacheSummary::default();
+    summary.record(entry("/page/1", 100, 0)).record(entry("/page/2", 200, 1));
+    assert!(summary.evict(300).is_empty());
+    assert_eq!(summary.total_bytes(), 300);
+}
+
+#[test]
+fn the_oldest_entries_are_evicted_first() {
+    let mut summary = EtagCacheSummary::default();
+    summary
+        .record(entry("/page/2", 100, 5))
+        .record(entry("/page/1", 100, 0))
+        .record(entry("/page/3", 100, 2))
+        .record(entry("/page/4", 100, 9));
+    assert_eq!(summary.evict(250), ["/page/1", "/page/3"]);
+    assert_eq!(urls(&summary), ["/page/2", "/page/4"]);
+    assert_eq!(summary.total_bytes(), 200);
+}
+
+#[test]
+fn a_single_entry_over_the_limit_is_evicted_too() {
+    let mut summary = EtagCacheSummary::default();
+    summary.record(entry("/huge", 500, 0));
+    assert_eq!(summary.evict(100), ["/huge"]);
+    assert!(summary.entries.is_empty());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{block_on, repo};
+use ice_repos::repository::RepoKey;
+use ice_repos::services::archive_repos::{set_archived, ArchiveOutcome};
+use ice_repos::services::fake_github_api::FakeGitHubApi;
+use ice_repos::services::github_api::{GitHubApiHandle, RepoSource, Team};
+use ice_repos::services::retry::RetryPolicy;
+
+fn pushed(day: u32) -> String {
+    format!("2022-08-{day:02}T12:00:00Z")
+}
+
+fn api() -> GitHubApiHandle {
+    GitHubApiHandle::new(FakeGitHubApi::new("alice")
+        .with_org_repos("example", (1..=5).rev().map(|id| repo(id).with_pushed_at(&pushed(id as u32)).build()).collect())
+        .with_team("example", Team { name: "Staff".to_owned(), slug: "staff".to_owned() }, &["repo-2", "repo-4"]))
+}
+
+fn names(api: &GitHubApiHandle, source: &RepoSource, page: usize, per_page: u8) -> (Vec<String>, usize) {
+    let repo_page = block_on(api.list_repos(source, page, per_page)).unwrap();
+    (repo_page.repos.into_iter().map(|repo| repo.name).collect(), repo_page.last_page)
+}
+
+#[test]
+fn pages_come_oldest_push_first() {
+    let api = api();
+    let source = RepoSource::Organization("example".to_owned());
+    assert_eq!(names(&api, &source, 1, 2), (vec!["repo-1".to_owned(), "repo-2".to_owned()], 3));
+    assert_eq!(names(&api, &source, 3, 2), (vec!["repo-5".to_owned()], 3));
+}
+
+#[test]
+fn teams_only_see_their_repositories() {
+    let api = api();
+    let source = RepoSource::Team { organization: "example".to_owned(), team: "staff".to_owned() };
+    assert_eq!(names(&api, &source, 1, 30), (vec!["repo-2".to_owned(), "repo-4".to_owned()], 1));
+}
+
+#[test]
+fn unknown_organizations_are_not_found() {
+    let api = api();
+    let result = block_on(api.list_repos(&RepoSource::Organization("nobody".to_owned()), 1, 30));
+    assert_eq!(result.unwrap_err().status(), Some(404));
+}
+
+#[test]
+fn archiving_changes_the_repository() {
+    let api = api();
+    let key = RepoKey::new("example", 3);
+    let result = block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(3).build(), true));
+    assert_eq!(result.outcome, ArchiveOutcome::Archived);
+    assert_eq!(result.owner, "example");
+    assert!(result.sent_request);
+    assert_eq!(result.status, Some(200));
+    assert!(block_on(api.get_repo("example", "repo-3")).unwrap().archived);
+}
+
+#[test]
+fn archiving_a_repository_someone_else_archived_is_already_done() {
+    let api = api();
+    let key = RepoKey::new("example", 3);
+    block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(3).build(), true));
+    // Our copy is from before it was archived, so we still send the request.
+    let result = block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(3).build(), true));
+    assert_eq!(result.outcome, ArchiveOutcome::AlreadyArchived);
+    assert!(result.sent_request);
+    assert_eq!(result.status, Some(403));
+}
+
+#[test]
+fn unarchiving_changes_the_repository_back() {
+    let api = GitHubApiHandle::new(FakeGitHubApi::new("alice")
+        .with_org_repos("example", vec![repo(1).with_archived(true).build()]));
+    let key = RepoKey::new("example", 1);
+    let result = block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(1).with_archived(true).build(), false));
+    assert_eq!(result.outcome, ArchiveOutcome::Unarchived);
+    assert!(!block_on(api.get_repo("example", "repo-1")).unwrap().archived);
+}
+
+#[test]
+fn repositories_already_as_wanted_are_left_alone() {
+    let api = api();
+    let key = RepoKey::new("example", 3);
+    let result = block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(3).build(), false));
+    assert_eq!(result.outcome, ArchiveOutcome::NotArchived);
+    assert!(!result.sent_request);
+    assert_eq!(result.status, None);
+}
+
+#[test]
+fn team_repositories_belong_to_the_organization() {
+    let api = api();
+    let key = RepoKey::new("example/staff", 2);
+    let result = block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(2).build(), true));
+    assert_eq!(result.outcome, ArchiveOutcome::Archived);
+    assert_eq!(result.owner, "example");
+}
+
+#[test]
+fn missing_repositories_are_not_found() {
+    let api = api();
+    let key = RepoKey::new("example", 9);
+    let result = block_on(set_archived(&*api, &RetryPolicy::default(), &key, &repo(9).build(), true));
+    assert_eq!(result.outcome, ArchiveOutcome::NotFound);
+    assert_eq!(result.status, Some(404));
+}
@@ -0,0 +1,66 @@
+use ice_repos::services::github_api::ApiError;
+
+fn from_response(status: u16, message: &str, retry_after: Option<u64>, sso_header: Option<&str>) -> ApiError {
+    ApiError::from_response(status, message.to_owned(), retry_after, sso_header)
+}
+
+#[test]
+fn a_401_is_unauthorized() {
+    assert_eq!(from_response(401, "Bad credentials", None, None), ApiError::Unauthorized("Bad credentials".to_owned()));
+}
+
+#[test]
+fn a_404_is_not_found() {
+    assert_eq!(from_response(404, "Not Found", None, None), ApiError::NotFound("Not Found".to_owned()));
+}
+
+#[test]
+fn a_429_is_rate_limited() {
+    assert_eq!(from_response(429, "Too many requests", Some(30), None),
+        ApiError::RateLimited { status: 429, message: "Too many requests".to_owned(), retry_after: Some(30) });
+    assert_eq!(from_response(429, "Too many requests", None, None).retry_after(), None);
+}
+
+#[test]
+fn a_403_about_secondary_rate_limits_is_rate_limited() {
+    let message = "You have exceeded a Secondary Rate Limit. Please wait a few minutes before you try again.";
+    assert_eq!(from_response(403, message, None, None),
+        ApiError::RateLimited { status: 403, message: message.to_owned(), retry_after: None });
+}
+
+#[test]
+fn a_403_with_retry_after_is_rate_limited() {
+    assert_eq!(from_response(403, "Slow down", Some(60), None),
+        ApiError::RateLimited { status: 403, message: "Slow down".to_owned(), retry_after: Some(60) });
+}
+
+#[test]
+fn other_403s_are_forbidden() {
+    assert_eq!(from_response(403, "Must have admin rights to Repository.", None, None),
+        ApiError::Forbidden { message: "Must have admin rights to Repository.".to_owned(), sso_url: None });
+}
+
+#[test]
+fn a_403_for_saml_sso_says_where_to_authorize() {
+    let err = from_response(403, "Resource protected by organization SAML enforcement.", None,
+        Some("required; url=https://github.com/orgs/example/sso?authorization_request=abc"));
+    assert_eq!(err, ApiError::Forbidden {
+        message: "Resource protected by organization SAML enforcement.".to_owned(),
+        sso_url: Some("https://github.com/orgs/example/sso?authorization_request=abc".to_owned())
+    });
+}
+
+#[test]
+fn a_403_for_saml_sso_without_a_url_is_still_forbidden() {
+    assert_eq!(from_response(403, "Resource protected by organization SAML enforcement.", None, Some("partial-results; organizations=21955855")),
+        ApiError::Forbidden { message: "Resource protected by organization SAML enforcement.".to_owned(), sso_url: None });
+    assert_eq!(from_response(403, "Forbidden", None, Some("required")),
+        ApiError::Forbidden { message: "Forbidden".to_owned(), sso_url: None });
+}
+
+#[test]
+fn anything_else_is_an_http_error() {
+    assert_eq!(from_response(422, "Validation Failed", None, None),
+        ApiError::Http { status: 422, message: "Validation Failed".to_owned(), retry_after: None });
+    assert_eq!(from_response(502, "Bad Gateway", Some(5), None).retry_after(), Some(5));
+}
@@ -0,0 +1,195 @@
+use chrono::{DateTime, Local};
+use ice_repos::repository::{RepoPermissions, Visibility};
+use ice_repos::services::github_api::{Affiliations, ApiError, RepoSource};
+use ice_repos::services::graphql_client::{parse_repos_page, repos_query};
+use serde_json::{json, Value};
+
+fn organization() -> RepoSource {
+    RepoSource::Organization("example".to_owned())
+}
+
+// Whether `query` opens as many braces as it closes.
+fn balanced(query: &str) -> bool {
+    query.matches('{').count() == query.matches('}').count()
+}
+
+#[test]
+fn organizations_are_looked_up_by_login() {
+    let source = organization();
+    let (query, variables) = repos_query(&source);
+    assert!(query.contains("query($first: Int!, $after: String, $login: String!)"));
+    assert!(query.contains("owner: organization(login: $login) {"));
+    assert!(query.contains("orderBy: {field: PUSHED_AT, direction: ASC})"));
+    assert_eq!(variables, [("login", "example")]);
+    assert!(balanced(&query));
+}
+
+#[test]
+fn users_only_list_repositories_they_own() {
+    let source = RepoSource::User("alice".to_owned());
+    let (query, variables) = repos_query(&source);
+    assert!(query.contains("owner: user(login: $login) {"));
+    assert!(query.contains("direction: ASC}, ownerAffiliations: [OWNER])"));
+    assert_eq!(variables, [("login", "alice")]);
+    assert!(balanced(&query));
+}
+
+#[test]
+fn the_logged_in_user_is_the_viewer() {
+    let affiliations = Affiliations { owner: true, collaborator: true, organization_member: false };
+    let source = RepoSource::AuthenticatedUser(affiliations);
+    let (query, variables) = repos_query(&source);
+    assert!(query.contains("query($first: Int!, $after: String)"));
+    assert!(query.contains("owner: viewer {"));
+    assert!(query.contains("affiliations: [OWNER, COLLABORATOR], ownerAffiliations: [OWNER, COLLABORATOR])"));
+    assert!(variables.is_empty());
+    assert!(balanced(&query));
+}
+
+#[test]
+fn teams_are_inside_their_organization() {
+    let source = RepoSource::Team { organization: "example".to_owned(), team: "staff".to_owned() };
+    let (query, variables) = repos_query(&source);
+    assert!(query.contains("query($first: Int!, $after: String, $login: String!, $team: String!)"));
+    assert!(query.contains("organization(login: $login) { owner: team(slug: $team) {"));
+    assert_eq!(variables, [("login", "example"), ("team", "staff")]);
+    assert!(balanced(&query));
+}
+
+fn node() -> Value {
+    json!({
+        "databaseId": 42,
+        "name": "lab-01",
+        "nameWithOwner": "example/lab-01",
+        "description": "The first lab",
+        "isArchived": false,
+        "updatedAt": "2022-08-02T12:00:00Z",
+        "pushedAt": "2022-08-01T12:00:00Z",
+        "isFork": true,
+        "isPrivate": true,
+        "visibility": "INTERNAL",
+        "isTemplate": true,
+        "url": "https://github.com/example/lab-01",
+        "diskUsage": 128,
+        "viewerPermission": "MAINTAIN",
+        "repositoryTopics": { "nodes": [{ "topic": { "name": "cs101" } }, { "topic": { "name": "lab" } }] },
+        "primaryLanguage": { "name": "Rust" },
+        "defaultBranchRef": { "name": "main" },
+        "issues": { "totalCount": 3 },
+        "pullRequests": { "totalCount": 2 }
+    })
+}
+
+fn response(nodes: &[Value], total_count: usize, end_cursor: Option<&str>, has_next_page: bool) -> String {
+    json!({
+        "data": {
+            "owner": {
+                "repositories": {
+                    "totalCount": total_count,
+                    "pageInfo": { "endCursor": end_cursor, "hasNextPage": has_next_page },
+                    "nodes": nodes
+                }
+            }
+        }
+    }).to_string()
+}
+
+fn date(date: &str) -> DateTime<Local> {
+    date.parse().unwrap()
+}
+
+#[test]
+fn nodes_become_repositories() {
+    let (page, _) = parse_repos_page(&organization(), &response(&[node()], 1, None, false), 30).unwrap();
+    let [repo] = page.repos.as_slice() else { panic!("expected one repository, got {:?}", page.repos) };
+    assert_eq!(repo.id, 42);
+    assert_eq!(repo.name, "lab-01");
+    assert_eq!(repo.full_name.as_deref(), Some("example/lab-01"));
+    assert_eq!(repo.owner(), Some("example"));
+    assert_eq!(repo.description.as_deref(), Some("The first lab"));
+    assert!(!repo.archived);
+    assert_eq!(repo.updated_at, date("2022-08-02T12:00:00Z"));
+    assert_eq!(repo.pushed_at, date("2022-08-01T12:00:00Z"));
+    assert!(repo.fork && repo.private && repo.is_template);
+    assert_eq!(repo.visibility, Some(Visibility::Internal));
+    assert_eq!(repo.topics, ["cs101", "lab"]);
+    assert_eq!(repo.language.as_deref(), Some("Rust"));
+    assert_eq!(repo.size, 128);
+    assert_eq!(repo.default_branch, "main");
+    assert_eq!(repo.open_issues_count, 5);
+    assert_eq!(repo.html_url, "https://github.com/example/lab-01");
+    assert_eq!(repo.permissions, Some(RepoPermissions { admin: false, maintain: true, push: true, triage: true, pull: true }));
+    assert!(!repo.can_archive());
+}
+
+#[test]
+fn empty_repositories_fill_in_what_graphql_leaves_null() {
+    let mut node = node();
+    for field in ["pushedAt", "diskUsage", "viewerPermission", "primaryLanguage", "defaultBranchRef", "description"] {
+        node[field] = Value::Null;
+    }
+    let (page, _) = parse_repos_page(&organization(), &response(&[node], 1, None, false), 30).unwrap();
+    let repo = &page.repos[0];
+    assert_eq!(repo.pushed_at, repo.updated_at);
+    assert_eq!(repo.size, 0);
+    assert_eq!(repo.permissions, None);
+    assert_eq!(repo.language, None);
+    assert_eq!(repo.default_branch, "");
+    assert_eq!(repo.description, None);
+}
+
+#[test]
+fn admins_can_archive() {
+    let mut node = node();
+    node["viewerPermission"] = json!("ADMIN");
+    let (page, _) = parse_repos_page(&organization(), &response(&[node], 1, None, false), 30).unwrap();
+    assert!(page.repos[0].can_archive());
+}
+
+#[test]
+fn readers_can_only_pull() {
+    let mut node = node();
+    node["viewerPermission"] = json!("READ");
+    let (page, _) = parse_repos_page(&organization(), &response(&[node], 1, None, false), 30).unwrap();
+    assert_eq!(page.repos[0].permissions, Some(RepoPermissions { pull: true, ..RepoPermissions::default() }));
+}
+
+#[test]
+fn the_total_count_gives_the_last_page() {
+    let (page, cursor) = parse_repos_page(&organization(), &response(&[node()], 61, Some("abc"), true), 30).unwrap();
+    assert_eq!(page.last_page, 3);
+    assert_eq!(cursor.as_deref(), Some("abc"));
+}
+
+#[test]
+fn there_is_no_cursor_after_the_last_page() {
+    let (page, cursor) = parse_repos_page(&organization(), &response(&[], 0, Some("abc"), false), 30).unwrap();
+    assert_eq!(page.last_page, 1);
+    assert_eq!(cursor, None);
+}
+
+#[test]
+fn team_repositories_are_inside_the_organization() {
+    let source = RepoSource::Team { organization: "example".to_owned(), team: "staff".to_owned() };
+    let text = json!({
+        "data": { "organization": { "owner": serde_json::from_str::<Value>(&response(&[node()], 1, None, false)).unwrap()["data"]["owner"] } }
+    }).to_string();
+    let (page, _) = parse_repos_page(&source, &text, 30).unwrap();
+    assert_eq!(page.repos[0].name, "lab-01");
+}
+
+#[test]
+fn missing_owners_are_not_found() {
+    let text = json!({ "data": { "owner": null } }).to_string();
+    let err = parse_repos_page(&organization(), &text, 30).unwrap_err();
+    assert_eq!(err, ApiError::NotFound("Couldn't find the organization example".to_owned()));
+}
+
+#[test]
+fn graphql_errors_are_sorted_by_type() {
+    let text = json!({
+        "data": null,
+        "errors": [{ "type": "NOT_FOUND", "message": "Could not resolve to an Organization with the login of 'nobody'." }]
+    }).to_string();
+    assert!(matches!(parse_repos_page(&organization(), &text, 30), Err(ApiError::NotFound(_))));
+}
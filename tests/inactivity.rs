@@ -0,0 +1,82 @@
+mod common;
+
+use chrono::{DateTime, Local, NaiveDate};
+use ice_repos::inactivity::{Activity, InactivityRule, Threshold};
+use ice_repos::repository::{DesiredState, DesiredStateMap, Phase, RepoKey};
+
+use common::repo;
+
+fn now() -> DateTime<Local> {
+    "2024-07-01T12:00:00Z".parse::<DateTime<Local>>().unwrap()
+}
+
+fn state_map() -> DesiredStateMap {
+    let mut state_map = DesiredStateMap::default();
+    state_map.with_repos("example", &[
+        repo(1).with_pushed_at("2022-01-01T12:00:00Z").build(),
+        repo(2).with_pushed_at("2024-06-20T12:00:00Z").build(),
+        repo(3).with_pushed_at("2021-01-01T12:00:00Z").with_archived(true).build(),
+    ]);
+    state_map
+}
+
+fn days(days: u32) -> InactivityRule {
+    InactivityRule { activity: Activity::Pushed, threshold: Threshold::DaysAgo(days) }
+}
+
+#[test]
+fn repositories_are_inactive_if_nothing_happened_since_the_cutoff() {
+    let old = repo(1).with_pushed_at("2024-05-01T12:00:00Z").with_updated_at("2024-06-01T12:00:00Z").build();
+    assert!(days(30).is_inactive(&old, now()));
+    assert!(!days(90).is_inactive(&old, now()));
+
+    let before = |date| InactivityRule { activity: Activity::Pushed, threshold: Threshold::Before(date) };
+    assert!(before(NaiveDate::from_ymd_opt(2024, 5, 2).unwrap()).is_inactive(&old, now()));
+    assert!(!before(NaiveDate::from_ymd_opt(2024, 4, 30).unwrap()).is_inactive(&old, now()));
+
+    // It was updated after it was last pushed to.
+    let updated = InactivityRule { activity: Activity::Updated, threshold: Threshold::DaysAgo(45) };
+    assert!(!updated.is_inactive(&old, now()));
+}
+
+#[test]
+fn rules_select_the_inactive_and_keep_the_rest() {
+    let mut state_map = state_map();
+    let changes = days(365).changes(state_map.get_repos_in("example"), now());
+    // The archived repository has nothing to decide.
+    assert_eq!(changes, vec![
+        (RepoKey::new("example", 1), DesiredState::Archive),
+        (RepoKey::new("example", 2), DesiredState::Keep),
+    ]);
+
+    assert!(state_map.transition_all(changes, Phase::Paginator).is_empty());
+    assert_eq!(state_map.get_desired_state(&RepoKey::new("example", 2)), Some(false));
+    // All in one step.
+    assert!(state_map.undo());
+    assert!(!state_map.can_undo());
+    assert_eq!(state_map.get_desired_state(&RepoKey::new("example", 2)), Some(true));
+}
+
+#[test]
+fn later_pages_can_start_out_selected_by_a_rule() {
+    let mut state_map = state_map();
+    let rule = days(365);
+    state_map.with_repos_selected_by("example", &[
+        repo(4).with_pushed_at("2020-01-01T12:00:00Z").build(),
+        repo(5).with_pushed_at("2024-06-30T12:00:00Z").build(),
+        repo(6).with_pushed_at("2020-01-01T12:00:00Z").with_archived(true).build(),
+    ], |repo| rule.is_inactive(repo, now()));
+
+    assert_eq!(state_map.get_desired_state(&RepoKey::new("example", 4)), Some(true));
+    assert_eq!(state_map.get_desired_state(&RepoKey::new("example", 5)), Some(false));
+    assert_eq!(state_map.map[&RepoKey::new("example", 6)].1, DesiredState::AlreadyArchived);
+    // Loading a page isn't something to undo.
+    assert!(!state_map.can_undo());
+}
+
+#[test]
+fn rules_describe_themselves() {
+    assert_eq!(days(1).describe(), "not pushed to in the last 1 day");
+    let rule = InactivityRule { activity: Activity::Updated, threshold: Threshold::Before(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()) };
+    assert_eq!(rule.describe(), "not updated since 2023-01-01");
+}
@@ -0,0 +1,209 @@
+use ice_repos::link_header::{self, Link, LinkHeaderError, PageLink, PaginationLinks};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const GITHUB_HEADER: &str = r#"<https://api.github.com/organizations/18425666/repos?page=1&per_page=5>; rel="prev", <https://api.github.com/organizations/18425666/repos?page=3&per_page=5>; rel="next", <https://api.github.com/organizations/18425666/repos?page=5&per_page=5>; rel="last", <https://api.github.com/organizations/18425666/repos?page=1&per_page=5>; rel="first""#;
+
+fn page_link(page: usize) -> PageLink {
+    PageLink {
+        url: format!("https://api.github.com/organizations/18425666/repos?page={page}&per_page=5"),
+        page: Some(page)
+    }
+}
+
+#[test]
+fn parses_a_github_header() {
+    let links = PaginationLinks::parse(GITHUB_HEADER).unwrap();
+    assert_eq!(links, PaginationLinks {
+        first: Some(page_link(1)),
+        prev: Some(page_link(1)),
+        next: Some(page_link(3)),
+        last: Some(page_link(5)),
+    });
+    assert_eq!(links.last_page(2), 5);
+}
+
+#[test]
+fn last_page_defaults_to_the_current_page() {
+    let header = r#"<https://api.github.com/organizations/1/repos?page=4>; rel="prev", <https://api.github.com/organizations/1/repos?page=1>; rel="first""#;
+    let links = PaginationLinks::parse(header).unwrap();
+    assert_eq!(links.next, None);
+    assert_eq!(links.last, None);
+    assert_eq!(links.last_page(5), 5);
+}
+
+#[test]
+fn empty_header_has_no_links() {
+    assert_eq!(link_header::parse("").unwrap(), vec![]);
+    assert_eq!(link_header::parse("  ,  , ").unwrap(), vec![]);
+    assert_eq!(PaginationLinks::parse("").unwrap(), PaginationLinks::default());
+}
+
+#[test]
+fn commas_and_semicolons_inside_urls() {
+    let links = link_header::parse(r#"<https://example.com/a,b;c?page=2>; rel="next", <https://example.com/x,y>; rel=last"#).unwrap();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].target, "https://example.com/a,b;c?page=2");
+    assert_eq!(links[0].page(), Ok(Some(2)));
+    assert_eq!(links[1].target, "https://example.com/x,y");
+    assert!(links[1].has_rel("last"));
+}
+
+#[test]
+fn quoted_parameters_can_hold_separators_and_escapes() {
+    let links = link_header::parse(r#"<https://example.com/>; title="a, b; \"c\" \\ d"; rel="next""#).unwrap();
+    assert_eq!(links, vec![Link {
+        target: "https://example.com/".to_owned(),
+        params: vec![
+            ("title".to_owned(), r#"a, b; "c" \ d"#.to_owned()),
+            ("rel".to_owned(), "next".to_owned()),
+        ]
+    }]);
+}
+
+#[test]
+fn relations_are_case_insensitive_and_can_be_combined() {
+    let links = PaginationLinks::parse(r#"<https://example.com/?page=7>; REL="Next Last""#).unwrap();
+    assert_eq!(links.next.as_ref().and_then(|link| link.page), Some(7));
+    assert_eq!(links.last.as_ref().and_then(|link| link.page), Some(7));
+    assert_eq!(links.first, None);
+}
+
+#[test]
+fn whitespace_is_optional() {
+    let links = link_header::parse("<a>;rel=next,<b>  ;  rel = \"last\"  ,<c>").unwrap();
+    let targets: Vec<_> = links.iter().map(|link| link.target.as_str()).collect();
+    assert_eq!(targets, ["a", "b", "c"]);
+    assert!(links[0].has_rel("next"));
+    assert!(links[1].has_rel("last"));
+    assert!(links[2].params.is_empty());
+}
+
+#[test]
+fn parameters_without_values() {
+    let links = link_header::parse(r#"<https://example.com/>; crossorigin; rel="next""#).unwrap();
+    assert_eq!(links[0].param("crossorigin"), Some(""));
+    assert!(links[0].has_rel("next"));
+}
+
+#[test]
+fn the_first_link_with_a_relation_wins() {
+    let links = PaginationLinks::parse("<?page=2>; rel=next, <?page=3>; rel=next").unwrap();
+    assert_eq!(links.next, Some(PageLink { url: "?page=2".to_owned(), page: Some(2) }));
+}
+
+#[test]
+fn relative_targets_still_have_page_numbers() {
+    let links = PaginationLinks::parse(r#"</orgs/ice-repos/repos?per_page=30&page=12>; rel="last""#).unwrap();
+    assert_eq!(links.last_page(1), 12);
+}
+
+#[test]
+fn links_without_a_page_parameter() {
+    let links = PaginationLinks::parse(r#"<https://example.com/repos?cursor=abc>; rel="next""#).unwrap();
+    assert_eq!(links.next.and_then(|link| link.page), None);
+}
+
+#[test]
+fn reports_malformed_headers() {
+    assert_eq!(link_header::parse(r#"https://example.com/; rel="next""#), Err(LinkHeaderError::ExpectedTarget(0)));
+    assert_eq!(link_header::parse(r#"<a>; rel="next", <https://example.com/"#), Err(LinkHeaderError::UnterminatedTarget(17)));
+    assert_eq!(link_header::parse(r#"<a>; rel="next"#), Err(LinkHeaderError::UnterminatedQuotedString(9)));
+    assert_eq!(link_header::parse(r#"<a> rel="next""#), Err(LinkHeaderError::ExpectedSeparator(4)));
+    assert_eq!(link_header::parse(r#"<a>; ="next""#), Err(LinkHeaderError::ExpectedParameterName(5)));
+    assert_eq!(
+        PaginationLinks::parse(r#"<https://example.com/?page=lots>; rel="last""#),
+        Err(LinkHeaderError::InvalidPageNumber {
+            target: "https://example.com/?page=lots".to_owned(),
+            page: "lots".to_owned()
+        })
+    );
+}
+
+// The remaining tests check properties over lots of randomly generated headers.
+// They use a fixed seed so any failure can be reproduced.
+
+const CASES: usize = 500;
+
+fn random_string(rng: &mut StdRng, alphabet: &[char], max_len: usize) -> String {
+    let len = rng.gen_range(0..=max_len);
+    (0..len).map(|_| alphabet[rng.gen_range(0..alphabet.len())]).collect()
+}
+
+fn random_link(rng: &mut StdRng) -> Link {
+    // Anything but `>` can go in a target, including the separators we split on.
+    let target_chars: Vec<char> = "abcXYZ019/:?&=.-_~%,; \"\\é".chars().collect();
+    let name_chars: Vec<char> = "abcxyz-_.*".chars().collect();
+    let value_chars: Vec<char> = "abc XYZ,;=<>\"\\é".chars().collect();
+    let target = random_string(rng, &target_chars, 40);
+    let params = (0..rng.gen_range(0..4))
+        .map(|_| {
+            let mut name = random_string(rng, &name_chars, 8);
+            if name.is_empty() {
+                name.push('r');
+            }
+            (name, random_string(rng, &value_chars, 16))
+        })
+        .collect();
+    Link { target, params }
+}
+
+fn join(links: &[Link]) -> String {
+    links.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+#[test]
+fn property_formatted_links_parse_back_to_themselves() {
+    let mut rng = StdRng::seed_from_u64(8288);
+    for _ in 0..CASES {
+        let links: Vec<Link> = (0..rng.gen_range(0..5)).map(|_| random_link(&mut rng)).collect();
+        let header = join(&links);
+        assert_eq!(link_header::parse(&header), Ok(links), "parsing {header:?}");
+    }
+}
+
+#[test]
+fn property_pagination_links_find_their_page_numbers() {
+    let mut rng = StdRng::seed_from_u64(42);
+    for _ in 0..CASES {
+        let pages: Vec<usize> = (0..4).map(|_| rng.gen_range(1..100_000)).collect();
+        let mut links: Vec<Link> = ["first", "prev", "next", "last"]
+            .iter()
+            .zip(&pages)
+            .map(|(rel, page)| Link {
+                target: format!("https://api.github.com/orgs/a,b/repos?per_page=30&page={page}"),
+                params: vec![("rel".to_owned(), (*rel).to_owned())]
+            })
+            .collect();
+        // Mix in links we don't care about, and shuffle the order.
+        for _ in 0..rng.gen_range(0..3) {
+            links.push(random_link(&mut rng));
+        }
+        for i in (1..links.len()).rev() {
+            links.swap(i, rng.gen_range(0..=i));
+        }
+        // Only look at headers where the random links don't happen to claim one
+        // of the relations, since then which one wins depends on the shuffle.
+        if links.iter().filter(|link| ["first", "prev", "next", "last"].iter().any(|rel| link.has_rel(rel))).count() != 4 {
+            continue
+        }
+        let header = join(&links);
+        let parsed = PaginationLinks::parse(&header).unwrap();
+        let found = [&parsed.first, &parsed.prev, &parsed.next, &parsed.last]
+            .map(|link| link.as_ref().and_then(|link| link.page));
+        assert_eq!(found.to_vec(), pages.iter().copied().map(Some).collect::<Vec<_>>(), "parsing {header:?}");
+        assert_eq!(parsed.last_page(1), pages[3]);
+    }
+}
+
+#[test]
+fn property_arbitrary_input_never_panics() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let alphabet: Vec<char> = "<>;,=\" \t\\abc?page=1é".chars().collect();
+    for _ in 0..CASES * 4 {
+        let header = random_string(&mut rng, &alphabet, 60);
+        // We don't care whether this succeeds, only that it doesn't panic.
+        let _ = PaginationLinks::parse(&header);
+    }
+}
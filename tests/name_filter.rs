@@ -0,0 +1,60 @@
+use ice_repos::name_filter::{NameFilter, PatternKind};
+
+fn glob(pattern: &str) -> impl Fn(&str) -> bool {
+    let matcher = NameFilter { kind: PatternKind::Glob, pattern: pattern.to_owned() }.matcher().unwrap();
+    move |name| matcher.matches(name)
+}
+
+#[test]
+fn an_empty_filter_matches_everything() {
+    let filter = NameFilter::default();
+    assert!(!filter.is_active());
+    assert!(filter.matcher().unwrap().matches("anything"));
+    assert!(!NameFilter { kind: PatternKind::Glob, pattern: "  ".to_owned() }.is_active());
+}
+
+#[test]
+fn stars_match_any_run_of_characters() {
+    let matches = glob("lab-0*");
+    assert!(matches("lab-01-alice"));
+    assert!(matches("lab-0"));
+    assert!(!matches("lab-10-alice"));
+    assert!(!matches("old-lab-01"));
+
+    let matches = glob("*-staff");
+    assert!(matches("lab-01-staff"));
+    assert!(!matches("lab-01-staff-notes"));
+
+    // Backtracking: the first `-` isn't the one that has to match.
+    assert!(glob("*-*-bob")("lab-01-bob"));
+    assert!(glob("*")(""));
+}
+
+#[test]
+fn question_marks_match_exactly_one_character() {
+    let matches = glob("lab-0?-*");
+    assert!(matches("lab-03-carol"));
+    assert!(!matches("lab-0-carol"));
+    assert!(!matches("lab-003-carol"));
+}
+
+#[test]
+fn brackets_match_classes_and_ranges() {
+    let matches = glob("lab-0[1-3]-*");
+    assert!(matches("lab-02-dave"));
+    assert!(!matches("lab-04-dave"));
+
+    let matches = glob("lab-0[!1-3]-*");
+    assert!(matches("lab-04-dave"));
+    assert!(!matches("lab-02-dave"));
+
+    assert!(glob("[]x]")("]"));
+    // An unclosed bracket is just a bracket.
+    assert!(glob("lab[")("lab["));
+}
+
+#[test]
+fn globs_ignore_case() {
+    assert!(glob("LAB-0*")("lab-01-Erin"));
+    assert!(glob("lab-0*")("Lab-01-erin"));
+}
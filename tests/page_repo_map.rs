@@ -0,0 +1,40 @@
+use ice_repos::page_repo_map::PageRepoMap;
+use ice_repos::services::github_api::{ListingBackend, RepoSource};
+
+fn page_map() -> PageRepoMap {
+    PageRepoMap::for_source(RepoSource::Organization("example".to_owned()), ListingBackend::Rest)
+}
+
+#[test]
+fn nothing_is_missing_until_we_know_how_many_pages_there_are() {
+    let page_map = page_map();
+    assert_eq!(page_map.first_missing_page(), None);
+    assert_eq!(page_map.loaded_page_count(), 0);
+}
+
+#[test]
+fn the_first_missing_page_is_the_lowest_one_not_loaded() {
+    let mut page_map = page_map();
+    page_map.add_page(1, vec![1, 2]);
+    page_map.set_last_page(4);
+    // The user jumped ahead to page 3 before the background loading got there.
+    page_map.add_page(3, vec![5, 6]);
+    assert_eq!(page_map.first_missing_page(), Some(2));
+
+    page_map.add_page(2, vec![3, 4]);
+    assert_eq!(page_map.first_missing_page(), Some(4));
+    page_map.add_page(4, vec![7]);
+    assert_eq!(page_map.first_missing_page(), None);
+    assert_eq!(page_map.loaded_page_count(), 4);
+}
+
+#[test]
+fn repositories_can_be_found_on_their_page() {
+    let mut page_map = page_map();
+    page_map.add_page(1, vec![1, 2]);
+    page_map.add_page(3, vec![5, 6]);
+    assert_eq!(page_map.page_of(6), Some(3));
+    assert_eq!(page_map.page_of(1), Some(1));
+    // Repository 3 is on page 2, which we haven't loaded.
+    assert_eq!(page_map.page_of(3), None);
+}
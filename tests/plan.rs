@@ -0,0 +1,173 @@
+mod common;
+
+use ice_repos::plan::{Plan, PlanEntry, PlanError, PlanFormat};
+use ice_repos::repository::{DesiredState, DesiredStateMap, Phase, RepoId, RepoKey};
+
+use common::repo;
+
+fn key(id: RepoId) -> RepoKey {
+    RepoKey::new("example", id)
+}
+
+/// Repositories 1 to 4, with 1 and 2 selected to archive, 3 kept, and 4 already archived.
+fn state_map() -> DesiredStateMap {
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).build(), repo(3).build(), repo(4).with_archived(true).build()]);
+    map.transition(&key(3), DesiredState::Keep, Phase::Paginator).unwrap();
+    map
+}
+
+fn entry(plan: &Plan, id: RepoId) -> &PlanEntry {
+    plan.entries.iter().find(|entry| entry.id == id).unwrap()
+}
+
+#[test]
+fn plans_include_every_loaded_repository() {
+    let plan = Plan::from_state_map(&state_map());
+
+    assert_eq!(plan.entries.len(), 4);
+    assert_eq!(entry(&plan, 1).desired_state, DesiredState::Archive);
+    assert_eq!(entry(&plan, 3).desired_state, DesiredState::Keep);
+    assert_eq!(entry(&plan, 4).desired_state, DesiredState::AlreadyArchived);
+    assert!(entry(&plan, 4).archived);
+    assert!(plan.entries.iter().all(|entry| entry.organization == "example"));
+}
+
+#[test]
+fn plans_round_trip_through_both_formats() {
+    let plan = Plan::from_state_map(&state_map());
+    for format in [PlanFormat::Json, PlanFormat::Csv] {
+        assert_eq!(Plan::parse(format, &plan.render(format)).unwrap(), plan, "{format:?}");
+    }
+}
+
+#[test]
+fn csv_fields_are_quoted_when_needed() {
+    let mut plan = Plan::from_state_map(&state_map());
+    plan.entries[0].name = "a, \"quoted\"\nname".to_owned();
+
+    let csv = plan.to_csv();
+    assert!(csv.contains("\"a, \"\"quoted\"\"\nname\""));
+    assert_eq!(Plan::from_csv(&csv).unwrap(), plan);
+}
+
+#[test]
+fn csv_columns_can_be_in_any_order() {
+    let csv = "name,id,organization,pushed_at,archived,desired_state\n\
+               repo-1,1,example,2022-08-01T12:00:00Z,FALSE,Archive\n\
+               \n";
+    let plan = Plan::from_csv(csv).unwrap();
+
+    assert_eq!(plan.entries.len(), 1);
+    assert_eq!(plan.entries[0].name, "repo-1");
+    assert_eq!(plan.entries[0].desired_state, DesiredState::Archive);
+    assert!(!plan.entries[0].archived);
+}
+
+#[test]
+fn bad_csv_is_reported_with_its_line() {
+    let header = "organization,id,name,desired_state,archived,pushed_at\n";
+    let bad_id = format!("{header}example,1,repo-1,Archive,false,2022-08-01T12:00:00Z\nexample,two,repo-2,Archive,false,2022-08-01T12:00:00Z\n");
+    assert!(matches!(Plan::from_csv(&bad_id), Err(PlanError::Csv { line: 3, .. })));
+
+    let bad_state = format!("{header}example,1,repo-1,Destroy,false,2022-08-01T12:00:00Z\n");
+    assert!(matches!(Plan::from_csv(&bad_state), Err(PlanError::Csv { line: 2, .. })));
+
+    let missing_column = "organization,id,name,desired_state,archived\n";
+    assert!(matches!(Plan::from_csv(missing_column), Err(PlanError::Csv { line: 1, .. })));
+
+    let unclosed_quote = format!("{header}example,1,\"repo-1,Archive,false,2022-08-01T12:00:00Z\n");
+    assert!(matches!(Plan::from_csv(&unclosed_quote), Err(PlanError::Csv { line: 2, .. })));
+
+    assert!(matches!(Plan::from_json("{\"entries\": [{}]}"), Err(PlanError::Json(_))));
+}
+
+#[test]
+fn applying_a_plan_copies_its_selection() {
+    let mut plan = Plan::from_state_map(&state_map());
+    plan.entries.iter_mut().find(|entry| entry.id == 1).unwrap().desired_state = DesiredState::Keep;
+    plan.entries.iter_mut().find(|entry| entry.id == 3).unwrap().desired_state = DesiredState::Archive;
+    plan.entries.iter_mut().find(|entry| entry.id == 4).unwrap().desired_state = DesiredState::Unarchive;
+
+    let mut map = state_map();
+    let report = plan.apply(&["example".to_owned()], &mut map);
+
+    assert_eq!(map.map[&key(1)].1, DesiredState::Keep);
+    assert_eq!(map.map[&key(2)].1, DesiredState::Archive);
+    assert_eq!(map.map[&key(3)].1, DesiredState::Archive);
+    assert_eq!(map.map[&key(4)].1, DesiredState::Unarchive);
+    let mut applied: Vec<RepoId> = report.applied.iter().map(|entry| entry.id).collect();
+    applied.sort_unstable();
+    assert_eq!(applied, vec![1, 3, 4]);
+    assert_eq!(report.unchanged.len(), 1);
+    assert!(!report.has_problems());
+
+    // The whole import is a single change.
+    map.undo();
+    assert_eq!(map.map, state_map().map);
+}
+
+#[test]
+fn plans_ignore_the_case_of_organization_names() {
+    let mut plan = Plan::from_state_map(&state_map());
+    for entry in &mut plan.entries {
+        entry.organization = "Example".to_owned();
+    }
+    plan.entries.iter_mut().find(|entry| entry.id == 1).unwrap().desired_state = DesiredState::Keep;
+    let mut gone = plan.entries[0].clone();
+    gone.id = 5;
+    plan.entries.push(gone);
+
+    let mut map = state_map();
+    let organizations = ["example".to_owned()];
+    let not_loaded: Vec<RepoKey> = plan.entries_not_in(&organizations, &map).iter()
+        .map(|entry| entry.loaded_key(&organizations).unwrap())
+        .collect();
+    assert_eq!(not_loaded, vec![key(5)]);
+
+    let report = plan.apply(&organizations, &mut map);
+    assert_eq!(map.map[&key(1)].1, DesiredState::Keep);
+    assert_eq!(report.applied.len(), 1);
+    assert_eq!(report.unchanged.len(), 3);
+    assert_eq!(report.missing.len(), 1);
+    assert!(report.other_organization.is_empty());
+    assert_eq!(map.map.len(), 4);
+}
+
+#[test]
+fn applying_a_plan_reports_entries_that_no_longer_fit() {
+    let mut plan = Plan::from_state_map(&state_map());
+    // Repository 5 isn't loaded (or doesn't exist).
+    let mut gone = plan.entries[0].clone();
+    gone.id = 5;
+    plan.entries.push(gone);
+    // This one's for another organization.
+    let mut elsewhere = plan.entries[0].clone();
+    elsewhere.organization = "elsewhere".to_owned();
+    plan.entries.push(elsewhere);
+
+    // Repository 2 has since been archived, and 1 has been archived by us.
+    let mut map = DesiredStateMap::default();
+    map.with_repos("example", &[repo(1).build(), repo(2).with_archived(true).build(), repo(3).build(), repo(4).with_archived(true).build()]);
+    map.transition(&key(1), DesiredState::Archived, Phase::Submission).unwrap();
+    map.transition(&key(3), DesiredState::Archive, Phase::Paginator).unwrap();
+    let report = plan.apply(&["example".to_owned()], &mut map);
+
+    let ids = |entries: &[PlanEntry]| entries.iter().map(|entry| entry.id).collect::<Vec<_>>();
+    assert_eq!(ids(&report.missing), vec![5]);
+    assert_eq!(ids(&report.other_organization), vec![1]);
+    assert_eq!(ids(&report.archive_status_changed), vec![1, 2]);
+    assert_eq!(ids(&report.applied), vec![3]);
+    assert_eq!(ids(&report.unchanged), vec![4]);
+    assert!(report.rejected.is_empty());
+    assert!(report.has_problems());
+    assert_eq!(map.map[&key(3)].1, DesiredState::Keep);
+}
+
+#[test]
+fn formats_are_recognized_from_file_names() {
+    assert_eq!(PlanFormat::from_file_name("plan.JSON"), Some(PlanFormat::Json));
+    assert_eq!(PlanFormat::from_file_name("ice-repos-plan.2022.csv"), Some(PlanFormat::Csv));
+    assert_eq!(PlanFormat::from_file_name("plan.txt"), None);
+    assert_eq!(PlanFormat::from_file_name("plan"), None);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use chrono::{DateTime, Local};
+use ice_repos::policy::{ArchivePolicy, Policy, PolicyAction, PolicyError, PolicyFormat};
+use ice_repos::repository::{DesiredState, DesiredStateMap, Phase, RepoId, RepoKey, Visibility};
+
+use common::repo;
+
+const TOML_POLICY: &str = r#"
+# The usual end of semester clean up.
+[[rules]]
+description = "Never touch templates"
+names = ["*-template", 'starter-*']
+action = "keep"
+
+[[rules]]
+pushed_within_days = 30   # still being worked on
+action = "keep"
+
+[[ rules ]]
+action = "archive"
+"#;
+
+fn key(id: RepoId) -> RepoKey {
+    RepoKey::new("example", id)
+}
+
+fn now() -> DateTime<Local> {
+    "2024-07-01T12:00:00Z".parse::<DateTime<Local>>().unwrap()
+}
+
+const OLD: &str = "2023-01-01T12:00:00Z";
+const RECENT: &str = "2024-06-20T12:00:00Z";
+
+fn state_map() -> DesiredStateMap {
+    let mut state_map = DesiredStateMap::default();
+    state_map.with_repos("example", &[
+        repo(1).with_name("lab-template").with_pushed_at(OLD).build(),
+        repo(2).with_name("Starter-Code").with_pushed_at(OLD).build(),
+        repo(3).with_name("lab-01-alice").with_pushed_at(RECENT).build(),
+        repo(4).with_name("lab-01-bob").with_pushed_at(OLD).build(),
+    ]);
+    state_map.transition(&key(1), DesiredState::Keep, Phase::Paginator).unwrap();
+    state_map
+}
+
+#[test]
+fn toml_and_json_policies_read_the_same() {
+    let from_toml = Policy::parse(PolicyFormat::Toml, TOML_POLICY).unwrap();
+    let from_json = Policy::parse(PolicyFormat::Json, r#"{"rules": [
+        {"description": "Never touch templates", "names": ["*-template", "starter-*"], "action": "keep"},
+        {"pushed_within_days": 30, "action": "keep"},
+        {"action": "archive"}
+    ]}"#).unwrap();
+    assert_eq!(from_toml, from_json);
+    assert_eq!(from_toml.rules.len(), 3);
+    assert_eq!(from_toml.rules[1].pushed_within_days, Some(30));
+    assert_eq!(from_toml.rules[2].action, PolicyAction::Archive);
+}
+
+#[test]
+fn toml_dates_booleans_and_visibility_are_understood() {
+    let policy = Policy::parse(PolicyFormat::Toml,
+        "[[rules]]\npushed_before = 2023-06-01\nfork = true\nvisibility = \"private\"\naction = \"archive\"").unwrap();
+    let rule = &policy.rules[0];
+    assert_eq!(rule.pushed_before.map(|date| date.to_string()), Some("2023-06-01".to_owned()));
+    assert_eq!(rule.fork, Some(true));
+    assert_eq!(rule.visibility, Some(Visibility::Private));
+    assert_eq!(Policy::parse(PolicyFormat::Json,
+        r#"{"rules": [{"pushed_before": "2023-06-01", "fork": true, "visibility": "private", "action": "archive"}]}"#), Ok(policy.clone()));
+
+    let mut fork = repo(1).with_name("fork").with_pushed_at(OLD).build();
+    assert!(!rule.matches(&fork, now()));
+    fork.fork = true;
+    fork.private = true;
+    assert!(rule.matches(&fork, now()));
+    fork.pushed_at = RECENT.parse().unwrap();
+    assert!(!rule.matches(&fork, now()));
+}
+
+#[test]
+fn any_toml_will_do() {
+    let policy = Policy::parse(PolicyFormat::Toml, r#"
+rules = [
+    { names = ["*-template", 'starter-*'], action = "keep" },
+    { description = '''
+Anything else
+that's old''', pushed_before = 2023-06-01, action = "archive" },
+]
+"#).unwrap();
+    assert_eq!(policy.rules.len(), 2);
+    assert_eq!(policy.rules[0].names, ["*-template", "starter-*"]);
+    assert_eq!(policy.rules[1].description.as_deref(), Some("Anything else\nthat's old"));
+    assert_eq!(policy.rules[1].pushed_before.map(|date| date.to_string()), Some("2023-06-01".to_owned()));
+}
+
+#[test]
+fn bad_policies_say_what_is_wrong() {
+    assert!(matches!(Policy::parse(PolicyFormat::Toml, "[[rules]]\naction = \"archive\"\naction = \"keep\""),
+        Err(PolicyError::Toml { line: 3, .. })));
+    assert!(matches!(Policy::parse(PolicyFormat::Toml, "[[rules]]\nnames = [\"a\" \"b\"]\naction = \"keep\""),
+        Err(PolicyError::Toml { line: 2, .. })));
+    assert!(matches!(Policy::parse(PolicyFormat::Toml, "[rules]\naction = \"keep\""),
+        Err(PolicyError::Toml { line: 1, .. })));
+    assert!(matches!(Policy::parse(PolicyFormat::Toml, "[[rules]]\npushed_before = 2023-06-01T12:00:00Z\naction = \"keep\""),
+        Err(PolicyError::Toml { line: 2, .. })));
+    // Typos in condition names aren't quietly ignored.
+    assert!(matches!(Policy::parse(PolicyFormat::Toml, "[[rules]]\npushed_whithin_days = 30\naction = \"keep\""),
+        Err(PolicyError::Toml { line: 2, .. })));
+    assert!(matches!(Policy::parse(PolicyFormat::Json, r#"{"rules": [{"names": ["*"]}]}"#), Err(PolicyError::Invalid(_))));
+    assert!(matches!(Policy::parse(PolicyFormat::Json, "{"), Err(PolicyError::Json(_))));
+    assert_eq!(PolicyFormat::from_file_name("semester.TOML"), Some(PolicyFormat::Toml));
+}
+
+#[test]
+fn the_first_matching_rule_decides() {
+    let mut state_map = state_map();
+    let mut archive_policy = ArchivePolicy::default();
+    archive_policy.load("semester.toml", Policy::parse(PolicyFormat::Toml, TOML_POLICY).unwrap());
+    let report = archive_policy.apply(&mut state_map, now());
+
+    assert_eq!((report.archive, report.keep, report.unmatched), (1, 3, 0));
+    assert!(report.rejected.is_empty());
+    assert_eq!(state_map.get_desired_state(&key(1)), Some(false));
+    assert_eq!(state_map.get_desired_state(&key(2)), Some(false));
+    assert_eq!(state_map.get_desired_state(&key(3)), Some(false));
+    assert_eq!(state_map.get_desired_state(&key(4)), Some(true));
+
+    assert_eq!(archive_policy.decided_by(&key(2), DesiredState::Keep).as_deref(), Some("rule 1 (Never touch templates)"));
+    assert_eq!(archive_policy.decided_by(&key(3), DesiredState::Keep).as_deref(), Some("rule 2"));
+    // Once the user changes it, the rule didn't decide it.
+    assert_eq!(archive_policy.decided_by(&key(3), DesiredState::Archive), None);
+
+    // Applying it was one step.
+    assert!(state_map.undo());
+    assert_eq!(state_map.get_desired_state(&key(2)), Some(true));
+    assert_eq!(state_map.get_desired_state(&key(3)), Some(true));
+    assert_eq!(state_map.get_desired_state(&key(1)), Some(false));
+}
+
+#[test]
+fn repositories_loaded_later_are_decided_too() {
+    let mut archive_policy = ArchivePolicy::default();
+    assert_eq!(archive_policy.decide_new(&key(5), &repo(5).with_name("lab-02-alice").with_pushed_at(OLD).build(), now()), None);
+
+    archive_policy.load("semester.toml", Policy::parse(PolicyFormat::Toml, TOML_POLICY).unwrap());
+    assert_eq!(archive_policy.decide_new(&key(5), &repo(5).with_name("lab-02-alice").with_pushed_at(OLD).build(), now()), Some(true));
+    assert_eq!(archive_policy.decide_new(&key(6), &repo(6).with_name("hw-template").with_pushed_at(OLD).build(), now()), Some(false));
+    assert_eq!(archive_policy.decided_by(&key(5), DesiredState::Archive).as_deref(), Some("rule 3"));
+
+    let unmatched = Policy::parse(PolicyFormat::Json, r#"{"rules": [{"names": ["lab-*"], "action": "archive"}]}"#).unwrap();
+    archive_policy.load("labs.json", unmatched);
+    assert_eq!(archive_policy.decide_new(&key(7), &repo(7).with_name("website").with_pushed_at(OLD).build(), now()), None);
+    assert_eq!(archive_policy.decided_by(&key(5), DesiredState::Archive), None);
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ice_repos::services::rate_limit::{RateLimit, RESERVE};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2022, 8, 1, 12, 0, 0).unwrap()
+}
+
+fn updated(mut rate_limit: RateLimit, headers: &[(&str, &str)]) -> RateLimit {
+    let headers: HashMap<&str, &str> = headers.iter().copied().collect();
+    rate_limit.update_from(|name| headers.get(name).map(|value| (*value).to_owned()));
+    rate_limit
+}
+
+fn remaining(remaining: u32, reset: DateTime<Utc>) -> RateLimit {
+    RateLimit { limit: Some(5000), remaining: Some(remaining), reset: Some(reset), paused_until: None }
+}
+
+#[test]
+fn headers_set_the_budget() {
+    let rate_limit = updated(RateLimit::default(), &[
+        ("x-ratelimit-limit", "5000"),
+        ("x-ratelimit-remaining", " 4999 "),
+        ("x-ratelimit-reset", &now().timestamp().to_string())
+    ]);
+    assert_eq!(rate_limit, remaining(4999, now()));
+}
+
+#[test]
+fn missing_headers_leave_the_budget_alone() {
+    assert_eq!(updated(remaining(100, now()), &[]), remaining(100, now()));
+    assert_eq!(updated(RateLimit::default(), &[]), RateLimit::default());
+}
+
+#[test]
+fn garbage_headers_leave_the_budget_alone() {
+    let rate_limit = updated(remaining(100, now()), &[
+        ("x-ratelimit-limit", "lots"),
+        ("x-ratelimit-remaining", "-1"),
+        ("x-ratelimit-reset", "")
+    ]);
+    assert_eq!(rate_limit, remaining(100, now()));
+}
+
+#[test]
+fn no_waiting_without_a_budget() {
+    assert_eq!(RateLimit::default().wait_time(now()), None);
+}
+
+#[test]
+fn no_waiting_while_there_is_budget_to_spare() {
+    assert_eq!(remaining(RESERVE + 1, now() + Duration::minutes(5)).wait_time(now()), None);
+}
+
+#[test]
+fn wait_until_the_reset_once_down_to_the_reserve() {
+    let reset = now() + Duration::minutes(5);
+    assert_eq!(remaining(RESERVE, reset).wait_time(now()), Some(Duration::minutes(5)));
+    assert!(!remaining(RESERVE, reset).is_exhausted());
+}
+
+#[test]
+fn wait_until_the_reset_when_out_of_budget() {
+    let reset = now() + Duration::seconds(30);
+    assert_eq!(remaining(0, reset).wait_time(now()), Some(Duration::seconds(30)));
+    assert!(remaining(0, reset).is_exhausted());
+}
+
+#[test]
+fn no_waiting_once_the_reset_has_passed() {
+    assert_eq!(remaining(0, now() - Duration::seconds(1)).wait_time(now()), None);
+    assert_eq!(remaining(0, now()).wait_time(now()), None);
+}
+
+#[test]
+fn no_waiting_without_a_reset_time() {
+    let rate_limit = RateLimit { remaining: Some(0), ..RateLimit::default() };
+    assert_eq!(rate_limit.wait_time(now()), None);
+}
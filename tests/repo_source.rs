@@ -0,0 +1,105 @@
+mod common;
+
+use ice_repos::repository::{Organizations, RepoKey};
+use ice_repos::services::github_api::{Affiliations, RepoSource};
+use ice_repos::services::rest_client::list_repos_url;
+
+use common::repo;
+
+#[test]
+fn each_source_lists_from_its_own_url() {
+    assert_eq!(list_repos_url(&RepoSource::Organization("example".to_owned()), 2, 30),
+        "/orgs/example/repos?sort=pushed&direction=asc&per_page=30&page=2");
+    assert_eq!(list_repos_url(&RepoSource::User("octocat".to_owned()), 1, 30),
+        "/users/octocat/repos?sort=pushed&direction=asc&per_page=30&page=1");
+    assert_eq!(list_repos_url(&RepoSource::AuthenticatedUser(Affiliations::default()), 1, 30),
+        "/user/repos?affiliation=owner,collaborator&sort=pushed&direction=asc&per_page=30&page=1");
+    // GitHub doesn't sort team repositories.
+    assert_eq!(list_repos_url(&RepoSource::Team { organization: "example".to_owned(), team: "cs-101".to_owned() }, 3, 30),
+        "/orgs/example/teams/cs-101/repos?per_page=30&page=3");
+}
+
+#[test]
+fn teams_are_filed_under_their_organization() {
+    let team = RepoSource::Team { organization: "example".to_owned(), team: "cs-101".to_owned() };
+    assert_eq!(team.name(), "example/cs-101");
+
+    let mut organizations = Organizations::default();
+    organizations.add("example").add_source(team.clone());
+    assert_eq!(organizations.names, vec!["example", "example/cs-101"]);
+    assert_eq!(organizations.source("example/cs-101"), team);
+
+    // Keys still split at the last `/`, and the team's organization owns its repositories.
+    let key: RepoKey = "example/cs-101/7".parse().unwrap();
+    assert_eq!(key, RepoKey::new("example/cs-101", 7));
+    assert_eq!(key.likely_owner(), "example");
+    assert_eq!(RepoKey::new("example", 7).likely_owner(), "example");
+}
+
+#[test]
+fn team_keys_round_trip() {
+    let key = RepoKey::new("example/cs-101", 7);
+    assert_eq!(key.to_string(), "example/cs-101/7");
+    assert_eq!(key.to_string().parse::<RepoKey>(), Ok(key.clone()));
+
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(json, r#""example/cs-101/7""#);
+    assert_eq!(serde_json::from_str::<RepoKey>(&json).unwrap(), key);
+
+    assert!("example".parse::<RepoKey>().is_err());
+    assert!("example/cs-101".parse::<RepoKey>().is_err());
+}
+
+#[test]
+fn affiliations_only_name_the_ones_turned_on() {
+    let affiliations = Affiliations { owner: false, collaborator: true, organization_member: true };
+    assert_eq!(affiliations.names(), vec!["collaborator", "organization_member"]);
+    assert!(Affiliations { owner: false, collaborator: false, organization_member: false }.names().is_empty());
+}
+
+#[test]
+fn sources_are_filed_under_their_names() {
+    let mut organizations = Organizations::default();
+    organizations.add("example");
+    organizations.add_source(RepoSource::User("octocat".to_owned()));
+    organizations.add_source(RepoSource::AuthenticatedUser(Affiliations::default()));
+
+    assert_eq!(organizations.names, vec!["example", "octocat", RepoSource::AUTHENTICATED_USER_NAME]);
+    assert_eq!(organizations.source("example"), RepoSource::Organization("example".to_owned()));
+    assert_eq!(organizations.source("octocat"), RepoSource::User("octocat".to_owned()));
+
+    // Changing the affiliations replaces the source rather than adding another.
+    let owned_only = Affiliations { owner: true, collaborator: false, organization_member: false };
+    organizations.add_source(RepoSource::AuthenticatedUser(owned_only));
+    assert_eq!(organizations.names.len(), 3);
+    assert_eq!(organizations.source(RepoSource::AUTHENTICATED_USER_NAME), RepoSource::AuthenticatedUser(owned_only));
+
+    organizations.remove("octocat");
+    assert_eq!(organizations.source("octocat"), RepoSource::Organization("octocat".to_owned()));
+}
+
+#[test]
+fn organization_names_ignore_case() {
+    let mut organizations = Organizations::default();
+    organizations.add("MyOrg").add_source(RepoSource::User("Octocat".to_owned()));
+    organizations.add("myorg");
+    assert_eq!(organizations.names, vec!["MyOrg", "Octocat"]);
+    assert_eq!(organizations.current.as_deref(), Some("MyOrg"));
+    assert!(organizations.contains("MYORG"));
+    assert_eq!(organizations.source("octocat"), RepoSource::User("Octocat".to_owned()));
+
+    organizations.remove("myorg");
+    assert_eq!(organizations.names, vec!["Octocat"]);
+    assert!(!organizations.contains("MyOrg"));
+    assert_eq!(organizations.current.as_deref(), Some("Octocat"));
+
+    organizations.remove("OCTOCAT");
+    assert!(organizations.names.is_empty() && organizations.sources.is_empty());
+    assert_eq!(organizations.current, None);
+}
+
+#[test]
+fn owners_come_from_the_full_name() {
+    assert_eq!(repo(1).with_full_name("octocat/repo-1").build().owner(), Some("octocat"));
+    assert_eq!(repo(1).build().owner(), None);
+}
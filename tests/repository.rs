@@ -0,0 +1,79 @@
+mod common;
+
+use common::repo;
+use ice_repos::repository::{RepoPermissions, Repository, Visibility};
+use serde_json::{json, Value};
+
+// A repository the way the REST API describes it, with `changes` made to it.
+fn from_rest(changes: &Value) -> Repository {
+    let mut value = serde_json::to_value(repo(1).build()).unwrap();
+    for (field, change) in changes.as_object().unwrap() {
+        if change.is_null() {
+            value.as_object_mut().unwrap().remove(field);
+        } else {
+            value[field] = change.clone();
+        }
+    }
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn rest_visibility_is_lowercase() {
+    for (name, visibility) in [("public", Visibility::Public), ("private", Visibility::Private), ("internal", Visibility::Internal)] {
+        let repo = from_rest(&json!({ "visibility": name }));
+        assert_eq!(repo.visibility, Some(visibility), "{name}");
+        assert_eq!(repo.visibility(), visibility, "{name}");
+        assert_eq!(visibility.label(), name);
+    }
+}
+
+#[test]
+fn graphql_visibility_is_uppercase() {
+    for (name, visibility) in [("PUBLIC", Visibility::Public), ("PRIVATE", Visibility::Private), ("INTERNAL", Visibility::Internal)] {
+        assert_eq!(serde_json::from_value::<Visibility>(json!(name)).unwrap(), visibility, "{name}");
+    }
+}
+
+#[test]
+fn visibility_is_saved_lowercase() {
+    assert_eq!(serde_json::to_value(Visibility::Internal).unwrap(), json!("internal"));
+}
+
+#[test]
+fn unknown_visibilities_are_rejected() {
+    assert!(serde_json::from_value::<Visibility>(json!("secret")).is_err());
+}
+
+#[test]
+fn without_visibility_private_decides() {
+    let repo = from_rest(&json!({ "visibility": null, "private": true }));
+    assert_eq!(repo.visibility, None);
+    assert_eq!(repo.visibility(), Visibility::Private);
+    assert_eq!(from_rest(&json!({ "visibility": null, "private": false })).visibility(), Visibility::Public);
+}
+
+#[test]
+fn visibility_wins_over_private() {
+    // GitHub marks internal repositories as private too.
+    assert_eq!(from_rest(&json!({ "visibility": "internal", "private": true })).visibility(), Visibility::Internal);
+}
+
+#[test]
+fn missing_permissions_mean_we_dont_know() {
+    let repo = from_rest(&json!({ "permissions": null }));
+    assert_eq!(repo.permissions, None);
+    assert!(repo.can_archive());
+}
+
+#[test]
+fn older_servers_only_send_some_permissions() {
+    let repo = from_rest(&json!({ "permissions": { "admin": true, "push": true, "pull": true } }));
+    assert_eq!(repo.permissions, Some(RepoPermissions { admin: true, maintain: false, push: true, triage: false, pull: true }));
+    assert!(repo.can_archive());
+}
+
+#[test]
+fn only_admins_can_archive() {
+    let repo = from_rest(&json!({ "permissions": { "admin": false, "maintain": true, "push": true, "triage": true, "pull": true } }));
+    assert!(!repo.can_archive());
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use std::cell::{Cell, RefCell};
+
+use chrono::{DateTime, TimeZone, Utc};
+use common::block_on;
+use ice_repos::services::github_api::ApiError;
+use ice_repos::services::retry::{parse_retry_after, FailureKind, RetryPolicy};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2022, 8, 1, 12, 0, 0).unwrap()
+}
+
+fn http(status: u16) -> ApiError {
+    ApiError::from_response(status, format!("HTTP {status}"), None, None)
+}
+
+fn secondary_rate_limit() -> ApiError {
+    ApiError::from_response(403, "You have exceeded a secondary rate limit.".to_owned(), None, None)
+}
+
+// Run `policy` against a request that fails with each of `errors` in turn
+// and then succeeds, returning the result, the retries, and how many
+// requests were sent.
+fn run(policy: &RetryPolicy, errors: Vec<ApiError>) -> (Result<u32, ApiError>, u32, u32) {
+    let errors = RefCell::new(errors.into_iter());
+    let sent = Cell::new(0);
+    let (result, retries) = block_on(policy.run(|| {
+        sent.set(sent.get() + 1);
+        let result = errors.borrow_mut().next().map_or(Ok(sent.get()), Err);
+        async move { result }
+    }));
+    (result, retries, sent.get())
+}
+
+#[test]
+fn retry_after_can_be_seconds() {
+    assert_eq!(parse_retry_after("120", now()), Some(120));
+    assert_eq!(parse_retry_after(" 0 ", now()), Some(0));
+}
+
+#[test]
+fn retry_after_can_be_an_http_date() {
+    assert_eq!(parse_retry_after("Mon, 01 Aug 2022 12:01:30 GMT", now()), Some(90));
+}
+
+#[test]
+fn retry_after_dates_in_the_past_mean_now() {
+    assert_eq!(parse_retry_after("Mon, 01 Aug 2022 11:00:00 GMT", now()), Some(0));
+}
+
+#[test]
+fn retry_after_garbage_is_ignored() {
+    assert_eq!(parse_retry_after("soon", now()), None);
+    assert_eq!(parse_retry_after("-5", now()), None);
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let policy = RetryPolicy::default();
+    let delays: Vec<u64> = (0..8).map(|retry| policy.delay_ms(retry, &http(502))).collect();
+    assert_eq!(delays, [1000, 2000, 4000, 8000, 16_000, 32_000, 60_000, 60_000]);
+    assert_eq!(policy.delay_ms(u32::MAX, &http(502)), 60_000);
+}
+
+#[test]
+fn backoff_does_what_github_says() {
+    let err = ApiError::from_response(503, "Unavailable".to_owned(), Some(7), None);
+    assert_eq!(RetryPolicy::default().delay_ms(3, &err), 7000);
+}
+
+#[test]
+fn secondary_rate_limits_wait_a_minute_by_default() {
+    assert_eq!(RetryPolicy::default().delay_ms(0, &secondary_rate_limit()), 60_000);
+}
+
+#[test]
+fn client_errors_are_permanent() {
+    for status in [403, 404, 422] {
+        assert_eq!(FailureKind::of(&http(status)), FailureKind::Permanent, "{status}");
+    }
+    assert_eq!(FailureKind::of(&ApiError::MalformedBody("{".to_owned())), FailureKind::Permanent);
+}
+
+#[test]
+fn server_errors_and_rate_limits_are_transient() {
+    for status in [429, 500, 502, 503, 504] {
+        assert_eq!(FailureKind::of(&http(status)), FailureKind::Transient, "{status}");
+    }
+    assert_eq!(FailureKind::of(&secondary_rate_limit()), FailureKind::Transient);
+    assert_eq!(FailureKind::of(&ApiError::Network("reset".to_owned())), FailureKind::Transient);
+}
+
+#[test]
+fn transient_failures_are_retried_until_they_succeed() {
+    assert_eq!(run(&RetryPolicy::default(), vec![http(502), http(503)]), (Ok(3), 2, 3));
+}
+
+#[test]
+fn secondary_rate_limits_are_retried() {
+    assert_eq!(run(&RetryPolicy::default(), vec![secondary_rate_limit()]), (Ok(2), 1, 2));
+}
+
+#[test]
+fn permanent_failures_are_not_retried() {
+    for status in [403, 404, 422] {
+        assert_eq!(run(&RetryPolicy::default(), vec![http(status)]), (Err(http(status)), 0, 1), "{status}");
+    }
+}
+
+#[test]
+fn retries_run_out() {
+    let policy = RetryPolicy { max_retries: 2, ..RetryPolicy::default() };
+    assert_eq!(run(&policy, vec![http(502), http(502), http(502), http(502)]), (Err(http(502)), 2, 3));
+}
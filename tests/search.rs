@@ -0,0 +1,39 @@
+mod common;
+
+use ice_repos::repository::{DesiredStateMap, RepoKey};
+use ice_repos::search::{is_query, search};
+
+use common::repo;
+
+fn state_map() -> DesiredStateMap {
+    let mut state_map = DesiredStateMap::default();
+    state_map.with_repos("example", &[
+        repo(1).with_name("lab-01-bob").with_description("Pair programming with Alice").build(),
+        repo(2).with_name("lab-01-alice").build(),
+        repo(3).with_name("website").with_description("The course website").build(),
+    ]);
+    state_map.with_repos("other", &[repo(4).with_name("Lab-02-Alice").with_description("Second lab").build()]);
+    state_map
+}
+
+#[test]
+fn names_and_descriptions_are_searched_in_every_organization() {
+    let results = search(&state_map(), "ALICE");
+    // Matches in the name come before those only in the description.
+    assert_eq!(results, vec![RepoKey::new("example", 2), RepoKey::new("other", 4), RepoKey::new("example", 1)]);
+    assert_eq!(search(&state_map(), "course"), vec![RepoKey::new("example", 3)]);
+}
+
+#[test]
+fn every_word_has_to_match_somewhere() {
+    assert_eq!(search(&state_map(), "alice second"), vec![RepoKey::new("other", 4)]);
+    assert_eq!(search(&state_map(), "lab-01 pair"), vec![RepoKey::new("example", 1)]);
+    assert!(search(&state_map(), "alice website").is_empty());
+}
+
+#[test]
+fn blank_queries_match_nothing() {
+    assert!(!is_query("  "));
+    assert!(search(&state_map(), "  ").is_empty());
+    assert!(is_query(" bob "));
+}
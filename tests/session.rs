@@ -0,0 +1,70 @@
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+
+use ice_repos::page_repo_map::PageRepoMap;
+use ice_repos::repository::{DesiredState, DesiredStateMap, Phase, RepoKey, SelectionMode};
+use ice_repos::services::github_api::{ListingBackend, RepoSource};
+use ice_repos::services::session::{describe_age, SavedSession};
+
+use common::repo;
+
+fn saved_session() -> SavedSession {
+    let mut desired_state_map = DesiredStateMap::default();
+    desired_state_map.with_repos("example", &[repo(1).build(), repo(2).build(), repo(3).build()]);
+    desired_state_map.transition(&RepoKey::new("example", 2), DesiredState::Keep, Phase::Paginator).unwrap();
+    let mut page_repo_map = PageRepoMap::for_source(RepoSource::Organization("example".to_owned()), ListingBackend::GraphQl);
+    page_repo_map.add_page(1, vec![1, 2, 3]);
+    page_repo_map.set_last_page(4);
+    SavedSession {
+        organization: "example".to_owned(),
+        backend: ListingBackend::GraphQl,
+        mode: SelectionMode::Archive,
+        desired_state_map,
+        page_repo_map,
+        saved_at: Utc.with_ymd_and_hms(2022, 8, 1, 12, 0, 0).unwrap()
+    }
+}
+
+#[test]
+fn a_saved_session_survives_a_round_trip_through_json() {
+    let session = saved_session();
+    let restored: SavedSession = serde_json::from_str(&serde_json::to_string(&session).unwrap()).unwrap();
+
+    assert_eq!(restored.organization, "example");
+    assert_eq!(restored.backend, ListingBackend::GraphQl);
+    assert_eq!(restored.desired_state_map.map, session.desired_state_map.map);
+    assert_eq!(restored.page_repo_map, session.page_repo_map);
+    assert!(restored.page_repo_map.is_for(&RepoSource::Organization("example".to_owned()), ListingBackend::GraphQl));
+    assert_eq!(restored.page_repo_map.last_page(), Some(4));
+    assert_eq!(restored.saved_at, session.saved_at);
+    assert_eq!(restored.selected_count(), 2);
+}
+
+#[test]
+fn undo_history_is_not_saved() {
+    let session = saved_session();
+    assert!(session.desired_state_map.can_undo());
+
+    let restored: SavedSession = serde_json::from_str(&serde_json::to_string(&session).unwrap()).unwrap();
+    assert!(!restored.desired_state_map.can_undo());
+}
+
+#[test]
+fn ages_are_described_in_the_largest_whole_unit() {
+    let then = Utc.with_ymd_and_hms(2022, 8, 1, 12, 0, 0).unwrap();
+    let age = |duration| describe_age(then, then + duration);
+
+    assert_eq!(age(Duration::seconds(30)), "just now");
+    assert_eq!(age(Duration::minutes(1)), "1 minute ago");
+    assert_eq!(age(Duration::minutes(59)), "59 minutes ago");
+    assert_eq!(age(Duration::hours(3)), "3 hours ago");
+    assert_eq!(age(Duration::days(1)), "1 day ago");
+    assert_eq!(age(Duration::days(12)), "12 days ago");
+    assert_eq!(age(Duration::days(30)), "30 days ago");
+    assert_eq!(age(Duration::days(31)), "1 month ago");
+    assert_eq!(age(Duration::days(364)), "11 months ago");
+    assert_eq!(age(Duration::days(365)), "1 year ago");
+    assert_eq!(age(Duration::days(400)), "1 year ago");
+    assert_eq!(age(Duration::days(3 * 365 + 1)), "3 years ago");
+}